@@ -0,0 +1,103 @@
+//! Mémoire occupée par pseudo suivi, représentation compacte vs ancienne
+//! (`DashMap<String, (Option<String>, Option<String>)>` + `Vec<String>`).
+//!
+//! `cargo bench --bench memory_per_name [-- <nombre de pseudos>]`
+
+use ahash::RandomState;
+use chrono::Utc;
+use claimer_rs_full::utilities::sql_management::{insert_name, UsernameMap};
+use dashmap::DashMap;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Pseudos synthétiques de 3 à 16 caractères, un sur deux en casse mixte.
+fn synthetic_name(i: usize) -> String {
+    let mut name = format!("{i:x}");
+    let len = 3 + i % 14;
+    name.extend("_abcdefghijklmno".chars().take(len.saturating_sub(name.len())));
+    if i.is_multiple_of(2) { name.to_uppercase() } else { name }
+}
+
+fn compact(n: usize) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let map: UsernameMap = DashMap::with_capacity_and_hasher_and_shard_amount(n, RandomState::new(), 1_024);
+    for i in 0..n {
+        insert_name(&map, &synthetic_name(i));
+    }
+    // état en régime : la moitié des pseudos sont pris
+    let now = Utc::now();
+    for (i, mut entry) in map.iter_mut().enumerate() {
+        if i.is_multiple_of(2) {
+            let display = entry.key().display().into_owned();
+            entry.set_uuid(Some(&format!("{:032x}", i as u128 + 1)));
+            entry.set_canonical(&display);
+        }
+        entry.set_last_seen(now);
+    }
+    let rotation: std::sync::Arc<[_]> = map.iter().map(|e| e.key().clone()).collect();
+    let used = ALLOCATED.load(Ordering::Relaxed) - before;
+    drop((map, rotation));
+    used
+}
+
+type LegacyMap = DashMap<String, (Option<String>, Option<String>), RandomState>;
+
+fn legacy(n: usize) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let map: LegacyMap = DashMap::with_capacity_and_hasher_and_shard_amount(n, RandomState::new(), 1_024);
+    for i in 0..n {
+        map.insert(synthetic_name(i), (None, None));
+    }
+    let now = Utc::now();
+    for (i, mut entry) in map.iter_mut().enumerate() {
+        if i.is_multiple_of(2) {
+            entry.0 = Some(format!("{:032x}", i as u128 + 1));
+        }
+        entry.1 = Some(now.to_rfc3339());
+    }
+    let rotation: Vec<String> = map.iter().map(|e| e.key().clone()).collect();
+    let used = ALLOCATED.load(Ordering::Relaxed) - before;
+    drop((map, rotation));
+    used
+}
+
+fn main() {
+    let n = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse::<usize>().ok())
+        .unwrap_or(1_000_000);
+
+    let compact_bytes = compact(n);
+    let legacy_bytes = legacy(n);
+
+    println!("pseudos suivis : {n}");
+    println!(
+        "compact : {:>8.1} octets/pseudo ({:.1} Mio)",
+        compact_bytes as f64 / n as f64,
+        compact_bytes as f64 / (1 << 20) as f64
+    );
+    println!(
+        "ancien  : {:>8.1} octets/pseudo ({:.1} Mio)",
+        legacy_bytes as f64 / n as f64,
+        legacy_bytes as f64 / (1 << 20) as f64
+    );
+}
@@ -1,29 +1,53 @@
 use ahash::RandomState;
 use chrono::prelude::*;
-use chrono_tz::Europe::Paris;
 use dashmap::DashMap;
-use rand::seq::IndexedRandom;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha12Rng;
 use reqwest::{Client, Proxy};
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::{Semaphore};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::Ordering;
 use tokio::time::{timeout, Duration};
 use rayon::prelude::*;
-use claimer_rs_full::utilities::sql_management::{init_hashmap_from_txt, update_batch_status, BATCH_SIZE};
-use claimer_rs_full::utilities::requests::fetch_batch;
-use claimer_rs_full::utilities::log_and_errors::send_webhook;
+use claimer_rs_full::utilities::sql_management::{update_batch_status, BATCH_SIZE};
+use claimer_rs_full::utilities::tags::{load_name_lists, TAGS};
+use claimer_rs_full::utilities::requests::{bisect_batch, fetch_batch, record_fetch};
+use claimer_rs_full::utilities::quarantine::{Quarantine, QUARANTINE_PATH};
+use claimer_rs_full::utilities::sql_management::{load_drop_windows, WindowMap, DROP_WINDOWS_PATH};
+use claimer_rs_full::utilities::config::CONFIG;
+use claimer_rs_full::utilities::time_display::{window_csv_header, window_csv_row, window_lines};
+use claimer_rs_full::utilities::username::Username;
+use claimer_rs_full::utilities::notifiers::{CheckpointStats, Dispatcher, Event, EventKind};
+use claimer_rs_full::utilities::outbox::{Outbox, OutboxStatus};
+use claimer_rs_full::utilities::storage::{open_db, DB_PATH};
+use claimer_rs_full::utilities::window_store::WindowStore;
+use claimer_rs_full::utilities::reminders::spawn_reminder_scheduler;
+use claimer_rs_full::utilities::escalation::spawn_escalation_scheduler;
+use claimer_rs_full::utilities::metrics::{spawn_metrics_server, MetricsSources, METRICS};
+use claimer_rs_full::utilities::calendar::{spawn_calendar_refresher, windows_to_ics, write_ics};
+use claimer_rs_full::utilities::clock::CLOCK;
+use claimer_rs_full::utilities::logging::{init_logging, init_logging_to, LogBuffer};
+use claimer_rs_full::utilities::proxy_management::{proxy_label, ProxyHealth};
+use claimer_rs_full::utilities::tui::{run_dashboard, DashboardSources};
+use claimer_rs_full::utilities::control::{spawn_api_server, ApiState, ScanControl};
+use claimer_rs_full::utilities::watchdog::{spawn_watchdog, Watchdog};
+use claimer_rs_full::utilities::stats::{StatsSnapshot, STATS, STATS_PATH};
+use claimer_rs_full::utilities::templates::format_uptime;
+use claimer_rs_full::utilities::reports::{rollup_csv_header, spawn_reports, Period, RollupStore};
+use claimer_rs_full::utilities::html_report::{render_html, HtmlReport};
+use once_cell::sync::Lazy;
+use tracing::{debug, debug_span, error, info, warn, Instrument};
 
 
 const NB_THREADS: usize = 7500;
+/// Requêtes simultanées au démarrage, modifiable via l'API.
+const MAX_IN_FLIGHT: usize = 150_000;
 
 pub async fn load_proxies(path: &str) -> Vec<String> {
     let proxy_file = Path::new(path);
     if !proxy_file.exists() {
-        println!("❗️ Fichier de proxy introuvable : {}", path);
+        error!(path, "fichier de proxy introuvable");
         return vec![];
     }
 
@@ -36,23 +60,78 @@ pub async fn load_proxies(path: &str) -> Vec<String> {
 }
 
 
-pub async fn process_batches(proxies: Vec<String>) {
-    let mut debut_programme = Utc::now();
-    let map_usernames = Arc::new(init_hashmap_from_txt("./names/3c.txt").expect("Failed to initialize map_usernames"));
-    let map_windows : Arc<DashMap<String, (String, String), RandomState>> = Arc::new(DashMap::with_capacity_and_hasher_and_shard_amount(70_000, RandomState::new(), 128));
-    let semaphore = Arc::new(Semaphore::new(150000));
-    let counter_200  = Arc::new(AtomicUsize::new(0));
-    let error_counter  = Arc::new(AtomicUsize::new(0));
-    let counter_429 = Arc::new(AtomicUsize::new(0));
-    let counter_403 = Arc::new(AtomicUsize::new(0));
-    let usernames = Arc::new(
-    map_usernames.iter().map(|e| e.key().clone()).collect::<Vec<_>>()
+/// Enregistre les événements détectés (SQLite, hors des threads du
+/// runtime) puis les confie au dispatcher.
+async fn publish(events: Vec<Event>, window_store: &Arc<WindowStore>, dispatcher: &Arc<Dispatcher>) {
+    if events.is_empty() {
+        return;
+    }
+    let store = window_store.clone();
+    let events = tokio::task::spawn_blocking(move || {
+        for event in &events {
+            if let Err(e) = store.record(event) {
+                error!(kind = ?event.kind(), error = %e, "fenêtre de drop non enregistrée");
+            }
+        }
+        events
+    })
+    .await
+    .expect("enregistrement des fenêtres interrompu");
+    for event in events {
+        match event.kind() {
+            EventKind::DropWindow => METRICS.drops_detected.fetch_add(1, Ordering::Relaxed),
+            EventKind::Claimed => METRICS.claims_detected.fetch_add(1, Ordering::Relaxed),
+            _ => 0,
+        };
+        dispatcher.emit(event).await;
+    }
+}
+
+/// Lance le scanner jusqu'au Ctrl-C, ou jusqu'à la fermeture du tableau de
+/// bord si `dashboard_logs` est fourni (mode `tui`).
+pub async fn process_batches(proxies: Vec<String>, dashboard_logs: Option<LogBuffer>) {
+    Lazy::force(&STATS); // l'uptime part du lancement du scanner
+    let map_usernames = Arc::new(load_name_lists(&TAGS, &CONFIG.lists, &CONFIG.name_tags).expect("Failed to initialize map_usernames"));
+    let map_windows : Arc<WindowMap> = Arc::new(DashMap::with_capacity_and_hasher_and_shard_amount(70_000, RandomState::new(), 128));
+    let quarantine = Arc::new(Quarantine::load(QUARANTINE_PATH).expect("Failed to load quarantine"));
+    let db = open_db(DB_PATH).expect("Failed to open database");
+    let outbox = Arc::new(Outbox::open(db.clone(), CONFIG.outbox.clone()).expect("Failed to open notification outbox"));
+    let dispatcher = Arc::new(Dispatcher::from_config(&CONFIG).with_outbox(outbox.clone()));
+    dispatcher.spawn_outbox_worker();
+    dispatcher.spawn_digest_flusher();
+    let window_store = Arc::new(WindowStore::open(db.clone()).expect("Failed to open drop window store"));
+    match load_drop_windows(DROP_WINDOWS_PATH).map(|records| window_store.import_if_empty(&records)) {
+        Ok(Ok(n)) if n > 0 => info!(count = n, path = DROP_WINDOWS_PATH, "fenêtres importées"),
+        Ok(Err(e)) => error!(path = DROP_WINDOWS_PATH, error = %e, "import des fenêtres impossible"),
+        Err(e) => error!(path = DROP_WINDOWS_PATH, error = %e, "lecture des fenêtres impossible"),
+        _ => {}
+    }
+    match window_store.load_into(&map_windows) {
+        Ok(n) if n > 0 => info!(count = n, "fenêtres de drop non reprises rechargées"),
+        Ok(_) => {}
+        Err(e) => error!(error = %e, "fenêtres de drop non rechargées"),
+    }
+    spawn_reminder_scheduler(window_store.clone(), dispatcher.clone(), CONFIG.reminders.clone());
+    spawn_escalation_scheduler(window_store.clone(), dispatcher.clone(), CONFIG.escalation.clone());
+    spawn_metrics_server(
+        &CONFIG.metrics,
+        MetricsSources { names: map_usernames.clone(), windows: window_store.clone(), quarantine: quarantine.clone() },
+    );
+    spawn_calendar_refresher(window_store.clone(), CONFIG.calendar.clone(), CONFIG.reminders.before_minutes.clone());
+    let control = Arc::new(ScanControl::new(map_usernames.clone(), MAX_IN_FLIGHT));
+    spawn_api_server(
+        &CONFIG.api,
+        ApiState {
+            control: control.clone(),
+            windows: window_store.clone(),
+            quarantine: quarantine.clone(),
+            outbox: Some(outbox.clone()),
+        },
     );
-    let total_batches = (usernames.len() + BATCH_SIZE - 1) / BATCH_SIZE;
     // print usernames and dashmap to be sure they are loaded
    
 
-    let clients: Vec<Client> = proxies
+    let (proxy_labels, clients): (Vec<String>, Vec<Client>) = proxies
     .par_iter()
     .take(10000)
     .filter_map(|proxy_url| {
@@ -62,196 +141,160 @@ pub async fn process_batches(proxies: Vec<String>) {
             .timeout(Duration::from_secs(3))
             .build()
             .ok()?;
-        Some(client)
+        Some((proxy_label(proxy_url), client))
     })
-    .collect();
+    .unzip();
     let clients = Arc::new(clients);
+    let proxy_health = Arc::new(ProxyHealth::new(proxy_labels));
+
+    let rollups = Arc::new(RollupStore::open(db.clone()).expect("Failed to open daily rollups"));
+    spawn_reports(
+        rollups,
+        window_store.clone(),
+        map_usernames.clone(),
+        dispatcher.clone(),
+        CONFIG.reports.clone(),
+        CONFIG.timezones()[0],
+    );
+
+    let watchdog = Arc::new(Watchdog::new(CONFIG.watchdog.clone(), Utc::now()));
+    spawn_watchdog(watchdog.clone(), control.clone(), dispatcher.clone());
 
     {
         tokio::spawn({
-            // on capture les compteurs & constantes
-            let counter_200 = counter_200.clone();
-            let counter_429 = counter_429.clone();
-            let counter_403 = counter_403.clone();
-            let error_counter = error_counter.clone();
-        
+            let quarantine = quarantine.clone();
+            let dispatcher = dispatcher.clone();
+
             async move {
-                // point de départ et état précédent
-                let mut last_instant     = Utc::now();
-        
                 loop {
                     tokio::time::sleep(Duration::from_secs(60)).await;          // ← 1 minute
-        
-                    // valeurs courantes
-                    let now      = Utc::now();
-                    let count_200 = counter_200.load(Ordering::Relaxed);
-                    let count_429 = counter_429.load(Ordering::Relaxed);
-                    let count_403 = counter_403.load(Ordering::Relaxed);
-                    let total_requests = count_200 + count_429 + count_403;
-                    let errors   = error_counter.load(Ordering::Relaxed);
-        
-                    // delta depuis le précédent rapport        
-                    // durée écoulée depuis le précédent rapport (≈ 60 s)
-                    let duration = now.signed_duration_since(last_instant).num_milliseconds() as f64 / 1000.0;
-                    let rpm      = if duration > 0.0 {
-                        count_200 as f64 / duration
-                    } else { 0.0 };
-
-
-                    let uptime_secs = now
-                    .signed_duration_since(debut_programme)
-                    .num_seconds();
-
-                    // Décompose `uptime_secs` en jours, heures, minutes, secondes
-                    let days    = uptime_secs / 86_400;
-                    let hours   = (uptime_secs % 86_400) / 3_600;
-                    let minutes = (uptime_secs % 3_600) / 60;
-                    let seconds =  uptime_secs % 60;
-        
-                    // taux cumulés (depuis le lancement)
-        
-                    // ───── affichage & webhook ─────
-                    println!(
-                        "\n | Checkpoint {:.0}s |
-                         \n | 200 : {} | {} %
-                         \n | 429 : {} | {} %
-                         \n | 403 : {} | {} %
-                         \n Erreurs de batch : {} ❌ {} %
-                         \n Req/s : {:.1} |
-                         \n Uptime : {} jours, {} heures, {} minutes, {} secondes",
-                        duration,
-                        count_200,
-                        if total_requests > 0 {
-                            (count_200 as f64 / total_requests as f64 * 100.0).round()
-                        } else { 0.0 },
-                        count_429,
-                        if total_requests > 0 {
-                            (count_429 as f64 / total_requests as f64 * 100.0).round()
-                        } else { 0.0 },
-                        count_403,
-                        if total_requests > 0 {
-                            (count_403 as f64 / total_requests as f64 * 100.0).round()
-                        } else { 0.0 },
-                        errors,
-                        if total_requests > 0 {
-                            errors as f64 / total_requests as f64 * 100.0
-                        } else { 0.0 },
-                        rpm,
-                        days,
-                        hours,
-                        minutes,
-                        seconds,
+
+                    let snapshot = STATS.snapshot();
+                    if let Err(e) = snapshot.save(STATS_PATH) {
+                        error!(path = STATS_PATH, error = %e, "écriture des statistiques impossible");
+                    }
+                    let minute = &snapshot.last_1m;
+                    info!(
+                        window_secs = minute.secs,
+                        requests = minute.counts.requests(),
+                        ok = minute.counts.ok,
+                        rate_limited = minute.counts.rate_limited,
+                        forbidden = minute.counts.forbidden,
+                        batches_failed = minute.counts.batches_failed,
+                        requests_per_sec = minute.requests_per_sec,
+                        batches_per_sec = minute.batches_per_sec,
+                        names_per_sec = minute.names_per_sec,
+                        quarantine_total = quarantine.len(),
+                        uptime_secs = snapshot.uptime_secs,
+                        "checkpoint"
                     );
-                    // Print all usernames that have not an UUID
-                    
-        
-                    
-
-                    let _ = send_webhook(&format!(
-                    "**Checkpoint** `{:.0}s`\
-                    \n\
-                    • 200  : `{}` ({}%)\n\
-                    • 429  : `{}` ({}%)\n\
-                    • 403  : `{}` ({}%)\n\
-                    • Err  : `{}` ({}%)\n\
-                    • RPS  : `{:.1}`\n\
-                    • UPT  : `{}D {:02}H {:02}m {:02}s`",
-                    duration,
-                    count_200,
-                    if total_requests > 0 {
-                        (count_200 as f64 / total_requests as f64 * 100.0).round()
-                    } else { 0.0 },
-                    count_429,
-                    if total_requests > 0 {
-                        (count_429 as f64 / total_requests as f64 * 100.0).round()
-                    } else { 0.0 },
-                    count_403,
-                    if total_requests > 0 {
-                        (count_403 as f64 / total_requests as f64 * 100.0).round()
-                    } else { 0.0 },
-                    errors,
-                    if total_requests > 0 {
-                        errors as f64 / total_requests as f64 * 100.0
-                    } else { 0.0 },
-                    rpm,
-                    days,
-                    hours,
-                    minutes,
-                    seconds
-                ))
-                .await;
-
-                // reset des compteurs
-                counter_200.store(0, Ordering::Relaxed);
-                counter_429.store(0, Ordering::Relaxed);
-                counter_403.store(0, Ordering::Relaxed);
-                error_counter.store(0, Ordering::Relaxed);
-                if days > 4 {
-                    debut_programme = Utc::now(); // reset le compteur de temps si > 4 jours
-                }
-                // on prépare le prochain tour
-                last_instant = now;
+                    dispatcher.emit(Event::Checkpoint {
+                        stats: CheckpointStats {
+                            snapshot: Box::new(snapshot.without_samples()),
+                            quarantine_total: quarantine.len(),
+                            quarantined: quarantine.drain_recent(),
+                        },
+                    })
+                    .await;
+                    watchdog.checkpoint_done();
                 }
             }
         });
     }
 
-    let ratio = usernames.len() as f64 / (NB_THREADS * BATCH_SIZE) as f64;
-    let max_loop = ratio.ceil() as usize;
     for batch in 0..NB_THREADS {
-        let semaphore = semaphore.clone();
+        let control = control.clone();
         let clients = clients.clone();
-        let counter_200 = counter_200.clone();
-        let counter_429 = counter_429.clone();
-        let counter_403 = counter_403.clone();
-        let error_counter = error_counter.clone();
+        let proxy_health = proxy_health.clone();
         let map_usernames = map_usernames.clone();
         let map_windows = map_windows.clone();
         let mut k = 0;
         let mut rng = ChaCha12Rng::from_os_rng();
-        let usernames_clone = usernames.clone();
+        let quarantine = quarantine.clone();
+        let dispatcher = dispatcher.clone();
+        let window_store = window_store.clone();
         tokio::spawn(async move {
             loop {
-                let batch_usernames: Vec<String> = usernames_clone.iter().cycle().skip((batch * BATCH_SIZE + k*NB_THREADS*BATCH_SIZE) % usernames_clone.len()).take(BATCH_SIZE).cloned().collect();
+                control.wait_while_paused().await;
+                // la rotation peut changer via l'API : bornes recalculées à chaque tour
+                let names_len = control.names_len();
+                if names_len == 0 {
+                    tokio::time::sleep(Duration::from_millis(550)).await;
+                    continue;
+                }
+                let max_loop = names_len.div_ceil(NB_THREADS * BATCH_SIZE);
+                k %= max_loop;
+                let offset = (batch * BATCH_SIZE + k*NB_THREADS*BATCH_SIZE) % names_len;
+                let total_batches = names_len.div_ceil(BATCH_SIZE);
+                let span = debug_span!("batch", worker = batch, batch_id = offset / BATCH_SIZE);
+                let mut batch_usernames: Vec<Username> = control.batch(offset, BATCH_SIZE);
+                batch_usernames.retain(|name| !quarantine.contains(name));
+                if batch_usernames.is_empty() {
+                    // tout le batch est en quarantaine
+                    tokio::time::sleep(Duration::from_millis(550)).await;
+                    k = (k + 1)%max_loop;
+                    continue;
+                }
                 // select random client
                 let mut error : bool = true;
+                let mut names_checked = 0;
+                let batch_start = std::time::Instant::now();
+                let mut attempts = 0;
                 for _retries in 1..=30{
-                    let client = clients.choose(&mut rng).expect("No clients available").clone();
-                    let _permit = semaphore.acquire().await.expect("Semaphore closed unexpectedly");
-                    match timeout(Duration::from_secs(5), fetch_batch(&client, &batch_usernames)).await {
-                        Ok(Ok((success, results,status))) => {
-                            let now = Utc::now().with_timezone(&Paris).to_rfc3339();
-                            if success {
-                                let converted: Vec<claimer_rs_full::utilities::sql_management::UsernameResult> = results.into_iter().map(|res| 
-                                    claimer_rs_full::utilities::sql_management::UsernameResult {
-                                        username: res.username,
-                                        uuid: res.uuid,
-                                        last_seen: now.clone(),
-                                    }
-                                ).collect();
-                                
-                                if let Ok(()) = update_batch_status(&map_usernames, &converted, &map_windows) {
-                                    let count = counter_200.fetch_add(1, Ordering::Relaxed) + 1;
-                                    print!("\r🔨 {}/{} batchs traités", count, total_batches);
-                                    error = false; // on a réussi
-                                    break;
-                                } 
-                            }
-                                if status == 429 {
-                                    let _ = counter_429.fetch_add(1, Ordering::Relaxed) + 1;
-                                } 
-                                if status == 403 {
-                                    let _= counter_403.fetch_add(1, Ordering::Relaxed) + 1;
+                    attempts += 1;
+                    assert!(!clients.is_empty(), "No clients available");
+                    let client_id = rng.random_range(0..clients.len());
+                    let client = clients[client_id].clone();
+                    let permit = control.semaphore().acquire().await.expect("Semaphore closed unexpectedly");
+                    let started = std::time::Instant::now();
+                    let fetched = timeout(Duration::from_secs(5), fetch_batch(&client, &batch_usernames))
+                        .instrument(span.clone())
+                        .await;
+                    let outcome = record_fetch(&fetched);
+                    proxy_health.record(client_id, outcome);
+                    span.in_scope(|| debug!(
+                        client_id,
+                        attempt = attempts,
+                        status = fetched.as_ref().ok().and_then(|r| r.as_ref().ok()).map(|r| r.status),
+                        error_class = fetched.as_ref().ok().and_then(|r| r.as_ref().ok()).and_then(|r| r.failure.as_ref()).map(|f| f.class.as_str()),
+                        outcome = outcome.as_str(),
+                        latency_ms = started.elapsed().as_millis() as u64,
+                        "requête"
+                    ));
+                    if let Ok(Ok(response)) = fetched {
+                        if response.success {
+                            names_checked = response.results.len();
+                            publish(update_batch_status(&map_usernames, &response.results, &map_windows), &window_store, &dispatcher).await;
+                            span.in_scope(|| debug!(names = names_checked, total = total_batches, "batch traité"));
+                            error = false; // on a réussi
+                            break;
+                        }
+                        if response.status == 400 {
+                            // inutile de réessayer tel quel : on isole le(s) pseudo(s) fautif(s)
+                            drop(permit);
+                            let outcome = bisect_batch(&clients, control.semaphore(), &mut rng, &batch_usernames, 5)
+                                .instrument(span.clone())
+                                .await;
+                            for (name, reason) in &outcome.invalid {
+                                match quarantine.insert(name, reason) {
+                                    Ok(true) => span.in_scope(|| warn!(username = %name, reason = %reason, "pseudo mis en quarantaine")),
+                                    Ok(false) => {}
+                                    Err(e) => error!(error = %e, "écriture de la quarantaine impossible"),
                                 }
-                                // if status == 699{
-                                //     println!("Erreur client : {:?}", client);
-                                // }
+                            }
+                            names_checked = outcome.results.len();
+                            publish(update_batch_status(&map_usernames, &outcome.results, &map_windows), &window_store, &dispatcher).await;
+                            error = !outcome.unresolved.is_empty();
+                            break;
                         }
-                        _ => {}
                     }
                 }
+                METRICS.record_batch(batch_start.elapsed().as_secs_f64(), attempts, !error);
+                STATS.record_batch(!error, names_checked);
                 if error 
-                    {error_counter.fetch_add(1, Ordering::Relaxed);}
+                    {
+                        span.in_scope(|| warn!(attempts, "batch abandonné"));
+                    }
                 tokio::time::sleep(Duration::from_millis(550)).await; 
                 k = (k + 1)%max_loop; 
             }
@@ -259,9 +302,26 @@ pub async fn process_batches(proxies: Vec<String>) {
         });
     }
 
-    tokio::signal::ctrl_c().await.expect("failed to listen for ctrl-c");
-    println!("\n🛑 Ctrl-C reçu → arrêt propre.");
-    
+    match dashboard_logs {
+        Some(logs) => {
+            let sources = DashboardSources {
+                names: map_usernames.clone(),
+                windows: window_store.clone(),
+                control: control.clone(),
+                proxies: proxy_health.clone(),
+                logs,
+            };
+            let dashboard = tokio::task::spawn_blocking(move || run_dashboard(sources)).await.map_err(std::io::Error::other);
+            if let Err(e) = dashboard.and_then(|result| result) {
+                error!(error = %e, "tableau de bord interrompu");
+            }
+            info!("tableau de bord fermé, arrêt propre");
+        }
+        None => {
+            tokio::signal::ctrl_c().await.expect("failed to listen for ctrl-c");
+            info!("Ctrl-C reçu, arrêt propre");
+        }
+    }
 }
     
 
@@ -269,24 +329,316 @@ pub async fn process_batches(proxies: Vec<String>) {
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 8)]
 async fn main() {
-    let os = std::env::consts::OS;
-    println!("OS: {}", os);
-    if let Err(e) = run_main().await {
-        eprintln!("❌ Error: {}", e);
+    // en mode `tui`, les logs vont au tableau de bord et non au terminal
+    let dashboard_logs = (std::env::args().nth(1).as_deref() == Some("tui")).then(LogBuffer::default);
+    match &dashboard_logs {
+        Some(logs) => init_logging_to(&CONFIG.logging, logs.clone()),
+        None => init_logging(&CONFIG.logging),
+    }
+    debug!(os = std::env::consts::OS, "démarrage");
+    CLOCK.configure(&CONFIG.clock);
+    if let Err(e) = run_main(dashboard_logs).await {
+        error!(error = %e, "arrêt sur erreur");
         
     }
     
 }
 
-async fn run_main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!(" Démarrage de la vérification des pseudos Minecraft...");
+async fn run_main(dashboard_logs: Option<LogBuffer>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("tui") => {}
+        Some("quarantine") => return quarantine_command(&args[1..]),
+        Some("windows") => return windows_command(&args[1..]),
+        Some("outbox") => return outbox_command(&args[1..]),
+        Some("export") => return export_command(&args[1..]),
+        Some("ack") => return ack_command(&args[1..]),
+        Some("stats") => return stats_command(&args[1..]),
+        Some("report") => return report_command(&args[1..]),
+        Some(other) => {
+            eprintln!("❓ Commande inconnue : {}", other);
+            eprintln!("Usage : claimer_rs_full [tui | quarantine [list | release <pseudo>] | windows [--csv] | outbox [list [pending|sent|dead] | show <id> | resend <id> | resend-dead] | export ics [--out <fichier>] | ack <pseudo>... | stats [--json] [--samples] | report [daily|weekly] [--date <jour>] [--json] | report export [--days <n>] [--csv] | report html [--days <n>] [--out <fichier>]]");
+            return Ok(());
+        }
+    }
+
+    info!("démarrage de la vérification des pseudos Minecraft");
     let proxies = load_proxies("proxies.txt").await;
     if proxies.is_empty() {
-        eprintln!("⚠️ Aucun proxy chargé. Vérifiez proxies.txt.");
+        error!("aucun proxy chargé, vérifiez proxies.txt");
+        return Ok(());
+    }
+
+    process_batches(proxies, dashboard_logs).await;
+    info!("vérification terminée");
+    Ok(())
+}
+
+fn quarantine_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let quarantine = Quarantine::load(QUARANTINE_PATH)?;
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            if quarantine.is_empty() {
+                println!("Aucun pseudo en quarantaine.");
+            }
+            for entry in quarantine.list() {
+                println!("{:<16} {}  {}", entry.username, entry.since.to_rfc3339(), entry.reason);
+            }
+        }
+        Some("release") => {
+            let Some(name) = args.get(1) else {
+                eprintln!("Usage : claimer_rs_full quarantine release <pseudo>");
+                return Ok(());
+            };
+            if quarantine.release(name)? {
+                // le fichier seul est réécrit : un scanner en cours garde sa copie
+                println!("✅ {} retiré de la quarantaine (pris en compte au prochain démarrage, ou tout de suite via DELETE /quarantine/{} de l'API)", name, name);
+            } else {
+                println!("{} n'est pas en quarantaine", name);
+            }
+        }
+        Some(other) => eprintln!("❓ Sous-commande inconnue : {}", other),
+    }
+    Ok(())
+}
+
+fn windows_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let zones = CONFIG.timezones();
+    let store = WindowStore::open(open_db(DB_PATH)?)?;
+    store.import_if_empty(&load_drop_windows(DROP_WINDOWS_PATH)?)?;
+    let mut windows = store.all(None)?;
+    windows.sort_by_key(|w| w.window.begin);
+
+    if args.iter().any(|a| a == "--csv") {
+        println!("{},status", window_csv_header(zones));
+        for stored in &windows {
+            println!("{},{:?}", window_csv_row(&stored.username, &stored.window, zones), stored.status);
+        }
+        return Ok(());
+    }
+
+    if windows.is_empty() {
+        println!("Aucune fenêtre de drop enregistrée.");
+    }
+    for stored in &windows {
+        match stored.tags.is_empty() {
+            true => println!("{} ({:?})", stored.username, stored.status),
+            false => println!("{} ({:?}) [{}]", stored.username, stored.status, stored.tags.join(", ")),
+        }
+        for line in window_lines(&stored.window, zones) {
+            println!("    {}", line);
+        }
+    }
+    Ok(())
+}
+
+fn outbox_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let outbox = Outbox::open(open_db(DB_PATH)?, CONFIG.outbox.clone())?;
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            let status = args.get(1).and_then(|s| OutboxStatus::parse(s));
+            let counts = outbox.counts()?;
+            println!(
+                "{}",
+                counts.iter().map(|(s, n)| format!("{}: {}", s, n)).collect::<Vec<_>>().join(" | ")
+            );
+            for entry in outbox.list(status, 50)? {
+                println!(
+                    "#{:<6} {:<8} {:<14} {:<16} essais {:<3} prochain {}  {}",
+                    entry.id,
+                    entry.status,
+                    format!("{:?}", entry.event.kind()),
+                    entry.sink,
+                    entry.attempts,
+                    entry.next_attempt_at.to_rfc3339(),
+                    entry.last_error.unwrap_or_default(),
+                );
+            }
+        }
+        Some("show") => {
+            let id: i64 = args.get(1).and_then(|s| s.parse().ok()).ok_or("Usage : outbox show <id>")?;
+            match outbox.get(id)? {
+                Some(entry) => {
+                    println!("{}", serde_json::to_string_pretty(&entry)?);
+                    for (filename, size) in outbox.attachment_sizes(id)? {
+                        println!("📎 {} ({} o)", filename, size);
+                    }
+                }
+                None => println!("Notification #{} introuvable", id),
+            }
+        }
+        Some("resend") => {
+            let id: i64 = args.get(1).and_then(|s| s.parse().ok()).ok_or("Usage : outbox resend <id>")?;
+            if outbox.resend(id)? {
+                println!("✅ Notification #{} remise en file (envoyée par le scanner en cours)", id);
+            } else {
+                println!("Notification #{} introuvable", id);
+            }
+        }
+        Some("resend-dead") => println!("✅ {} notification(s) remise(s) en file", outbox.resend_dead()?),
+        Some(other) => eprintln!("❓ Sous-commande inconnue : {}", other),
+    }
+    Ok(())
+}
+
+fn ack_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if args.is_empty() {
+        eprintln!("Usage : claimer_rs_full ack <pseudo>...");
+        return Ok(());
+    }
+    let store = WindowStore::open(open_db(DB_PATH)?)?;
+    for name in args {
+        match store.acknowledge(name)? {
+            0 => println!("ℹ️ Aucune alerte en attente pour {}", name),
+            n => println!("✅ {} fenêtre(s) de {} prise(s) en compte, escalade arrêtée", n, name),
+        }
+    }
+    Ok(())
+}
+
+fn stats_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let snapshot = match StatsSnapshot::load(STATS_PATH) {
+        Ok(snapshot) => snapshot,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("Aucune statistique : le scanner écrit {} à chaque checkpoint.", STATS_PATH);
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if args.iter().any(|a| a == "--json") {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+        return Ok(());
+    }
+
+    println!("Statistiques au {} (uptime {})", snapshot.at.to_rfc3339(), format_uptime(snapshot.uptime_secs));
+    println!("{:>8} {:>9} {:>8} {:>8} {:>8} {:>8} {:>10} {:>8} {:>9} {:>10}",
+        "fenêtre", "requêtes", "200", "429", "403", "autres", "batchs", "échecs", "req/s", "pseudos/s");
+    for (label, window) in [("1 min", &snapshot.last_1m), ("15 min", &snapshot.last_15m), ("1 h", &snapshot.last_1h)] {
+        let c = &window.counts;
+        println!("{:>8} {:>9} {:>8} {:>8} {:>8} {:>8} {:>10} {:>8} {:>9.1} {:>10.1}",
+            label, c.requests(), c.ok, c.rate_limited, c.forbidden, c.bad_request + c.errors + c.timeouts,
+            c.batches(), c.batches_failed, window.requests_per_sec, window.names_per_sec);
+    }
+    let c = &snapshot.lifetime;
+    println!("{:>8} {:>9} {:>8} {:>8} {:>8} {:>8} {:>10} {:>8}",
+        "total", c.requests(), c.ok, c.rate_limited, c.forbidden, c.bad_request + c.errors + c.timeouts,
+        c.batches(), c.batches_failed);
+    println!("Pseudos vérifiés depuis le démarrage : {}", c.names_checked);
+    if let Some(skew) = snapshot.clock_skew_ms {
+        println!("Décalage d'horloge (serveur − local) : {:+.1} s", skew as f64 / 1000.0);
+    }
+
+    let samples = args.iter().any(|a| a == "--samples");
+    let failures: Vec<_> = snapshot.failures.iter().filter(|f| f.count > 0).collect();
+    if !failures.is_empty() {
+        println!("Échecs par classe :");
+    }
+    for failure in failures {
+        println!("{:>22} {:>8}", failure.class.label(), failure.count);
+        if samples {
+            for sample in &failure.samples {
+                println!("    {} {}", sample.at.to_rfc3339(), sample.detail);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn report_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let store = RollupStore::open(open_db(DB_PATH)?)?;
+    let tz = CONFIG.timezones()[0];
+    let today = Utc::now().with_timezone(&tz).date_naive();
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+
+    let days: i64 = option("--days").map(|n| n.parse()).transpose()?.unwrap_or(30);
+    let from = today - chrono::Duration::days(days.max(1) - 1);
+
+    if args.first().map(String::as_str) == Some("html") {
+        let windows = WindowStore::open(open_db(DB_PATH)?)?;
+        windows.import_if_empty(&load_drop_windows(DROP_WINDOWS_PATH)?)?;
+        let html = render_html(
+            &HtmlReport {
+                windows: &windows.all(None)?,
+                rollups: &store.days(from, today)?,
+                from,
+                to: today,
+                quarantine: &Quarantine::load(QUARANTINE_PATH)?.list(),
+            },
+            tz,
+            Utc::now(),
+        );
+        match option("--out") {
+            Some(path) => {
+                fs::write(path, html)?;
+                println!("📄 Rapport écrit dans {}", path);
+            }
+            None => print!("{}", html),
+        }
+        return Ok(());
+    }
+
+    if args.first().map(String::as_str) == Some("export") {
+        let rollups = store.days(from, today)?;
+        if args.iter().any(|a| a == "--csv") {
+            println!("{}", rollup_csv_header());
+            rollups.iter().for_each(|day| println!("{}", day.csv_row()));
+        } else {
+            println!("{}", serde_json::to_string_pretty(&rollups)?);
+        }
+        return Ok(());
+    }
+
+    let period = match args.first().map(String::as_str) {
+        None => Period::Daily,
+        Some(raw) if raw.starts_with("--") => Period::Daily,
+        Some(raw) => match Period::parse(raw) {
+            Some(period) => period,
+            None => {
+                eprintln!("❓ Sous-commande inconnue : {}", raw);
+                return Ok(());
+            }
+        },
+    };
+    // par défaut, la période en cours jusqu'à aujourd'hui inclus
+    let to = option("--date").map(|day| day.parse::<NaiveDate>()).transpose()?.unwrap_or(today);
+    let summary = store.summary(period, to)?;
+    if args.iter().any(|a| a == "--json") {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
         return Ok(());
     }
 
-    process_batches(proxies).await;
-    println!("Vérification terminée.");
+    println!("Résumé {} du {} au {} ({})", period.label(), summary.from, summary.to, tz.name());
+    if summary.days.is_empty() {
+        println!("Aucune donnée : le scanner enregistre un bilan par jour dans {}.", DB_PATH);
+        return Ok(());
+    }
+    println!("{:>10} {:>10} {:>7} {:>8} {:>8} {:>9} {:>7} {:>6} {:>9} {:>10}",
+        "jour", "requêtes", "% 200", "batchs", "échecs", "pseudos", "drops", "pris", "fenêtres", "couverture");
+    let rows = summary.days.iter().map(|day| (day.day.to_string(), &day.rollup));
+    for (label, r) in rows.chain([("total".to_string(), &summary.totals)]) {
+        let c = &r.counts;
+        println!("{:>10} {:>10} {:>7.1} {:>8} {:>8} {:>9} {:>7} {:>6} {:>9} {:>9.1}%",
+            label, c.requests(), r.success_pct(), c.batches(), c.batches_failed, c.names_checked,
+            r.drops_detected, r.claims_observed, format!("{}/{}", r.windows_opened, r.windows_closed), r.coverage_pct());
+    }
+    Ok(())
+}
+
+fn export_command(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if args.first().map(String::as_str) != Some("ics") {
+        eprintln!("Usage : export ics [--out <fichier>]");
+        return Ok(());
+    }
+    let store = WindowStore::open(open_db(DB_PATH)?)?;
+    store.import_if_empty(&load_drop_windows(DROP_WINDOWS_PATH)?)?;
+    let ics = windows_to_ics(&store.all(None)?, &CONFIG.reminders.before_minutes, Utc::now());
+
+    match args.iter().position(|a| a == "--out").and_then(|i| args.get(i + 1)) {
+        Some(path) => {
+            write_ics(path, &ics)?;
+            println!("📅 Calendrier écrit dans {}", path);
+        }
+        None => print!("{}", ics),
+    }
     Ok(())
 }
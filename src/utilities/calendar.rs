@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::error;
+
+use super::time_display::unix_micros;
+use super::username::Username;
+use super::window_store::{StoredWindow, WindowStatus, WindowStore};
+
+/// Fichier `.ics` régénéré en continu par le scanner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Chemin du fichier, aucun fichier si absent.
+    pub path: Option<String>,
+    pub refresh_secs: u64,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self { path: None, refresh_secs: 60 }
+    }
+}
+
+/// Calendrier iCalendar (RFC 5545) des fenêtres : un VEVENT par fenêtre, avec
+/// une alarme par décalage de `alarms_minutes` tant qu'elle est ouverte.
+/// Une fenêtre prise passe en `STATUS:CANCELLED` ; `SEQUENCE` augmente quand
+/// la fenêtre change d'état pour que les clients mettent l'événement à jour.
+pub fn windows_to_ics(windows: &[StoredWindow], alarms_minutes: &[i64], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//claimer_rs_full//drop windows//FR".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Fenêtres de drop".to_string(),
+    ];
+
+    for stored in windows {
+        let window = &stored.window;
+        let (status, sequence) = match stored.status {
+            WindowStatus::Open => ("CONFIRMED", 0),
+            WindowStatus::Closed => ("CONFIRMED", 1),
+            WindowStatus::Claimed => ("CANCELLED", 1),
+        };
+        let summary = match stored.status {
+            WindowStatus::Claimed => format!("Drop : {} (pris)", stored.username),
+            _ => format!("Drop : {}", stored.username),
+        };
+        let description = format!(
+            "Fenêtre de drop de {}.\nUnix : {} → {}",
+            stored.username,
+            unix_micros(window.begin),
+            unix_micros(window.end)
+        );
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-{}@claimer_rs_full",
+            Username::key_of(&stored.username),
+            window.begin.timestamp_micros()
+        ));
+        lines.push(format!("DTSTAMP:{}", ics_time(now)));
+        lines.push(format!("DTSTART:{}", ics_time(window.begin)));
+        lines.push(format!("DTEND:{}", ics_time(window.end)));
+        lines.push(format!("LAST-MODIFIED:{}", ics_time(stored.closed_at.unwrap_or(stored.detected_at))));
+        lines.push(format!("SUMMARY:{}", escape(&summary)));
+        lines.push(format!("DESCRIPTION:{}", escape(&description)));
+        let categories: Vec<String> =
+            std::iter::once("drop").chain(stored.tags.iter().map(String::as_str)).map(escape).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        lines.push(format!("STATUS:{}", status));
+        lines.push(format!("SEQUENCE:{}", sequence));
+        if stored.status == WindowStatus::Open {
+            for minutes in alarms_minutes {
+                lines.push("BEGIN:VALARM".to_string());
+                lines.push("ACTION:DISPLAY".to_string());
+                lines.push(format!("TRIGGER:-PT{}M", minutes));
+                lines.push(format!("DESCRIPTION:{}", escape(&summary)));
+                lines.push("END:VALARM".to_string());
+            }
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        fold(&line, &mut ics);
+    }
+    ics
+}
+
+fn ics_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Échappement des valeurs TEXT (RFC 5545 §3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Ligne terminée par CRLF, repliée à 75 octets sans couper un caractère.
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Écrit le fichier via un fichier temporaire renommé, pour qu'un client qui
+/// le lit ne tombe jamais sur un calendrier à moitié écrit.
+pub fn write_ics(path: impl AsRef<Path>, ics: &str) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let tmp = path.with_extension("ics.tmp");
+    std::fs::write(&tmp, ics)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Régénère `config.path` toutes les `refresh_secs` secondes quand les
+/// fenêtres changent (nouvelle fenêtre, prise, fin).
+pub fn spawn_calendar_refresher(store: Arc<WindowStore>, config: CalendarConfig, alarms_minutes: Vec<i64>) {
+    let Some(path) = config.path.clone() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut last = String::new();
+        loop {
+            let reader = store.clone();
+            match tokio::task::spawn_blocking(move || reader.all(None)).await.expect("lecture des fenêtres interrompue") {
+                Ok(windows) => {
+                    // DTSTAMP exclu de la comparaison : il change à chaque tour
+                    let ics = windows_to_ics(&windows, &alarms_minutes, Utc::now());
+                    let fingerprint: String = ics.lines().filter(|l| !l.starts_with("DTSTAMP:")).collect();
+                    if fingerprint != last {
+                        match write_ics(&path, &ics) {
+                            Ok(()) => last = fingerprint,
+                            Err(e) => error!(path = %path, error = %e, "écriture du calendrier impossible"),
+                        }
+                    }
+                }
+                Err(e) => error!(error = %e, "lecture des fenêtres impossible"),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(config.refresh_secs.max(1))).await;
+        }
+    });
+}
+
+#[test]
+fn test_windows_to_ics() {
+    use super::sql_management::DropWindow;
+
+    let begin = DateTime::parse_from_rfc3339("2025-07-14T19:03:07.25Z").unwrap().with_timezone(&Utc);
+    let window = DropWindow { begin, end: begin + chrono::Duration::seconds(90) };
+    let open = StoredWindow {
+        username: "Dream".into(),
+        window,
+        status: WindowStatus::Open,
+        detected_at: begin,
+        closed_at: None,
+        tags: vec!["3c".into()],
+        acknowledged_at: None,
+        escalated_at: None,
+        escalations: 0,
+    };
+    let claimed = StoredWindow {
+        username: "jeb_avec_un_pseudo_bien_trop_long_pour_tenir_sur_une_seule_ligne".into(),
+        status: WindowStatus::Claimed,
+        closed_at: Some(begin),
+        ..open.clone()
+    };
+
+    let ics = windows_to_ics(&[open, claimed], &[60, 5], begin);
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains("UID:dream-1752519787250000@claimer_rs_full\r\n"));
+    assert!(ics.contains("DTSTART:20250714T190307Z\r\nDTEND:20250714T190437Z\r\n"));
+    // ligne de plus de 75 octets repliée : CRLF puis une espace
+    let summary = "SUMMARY:Drop : jeb_avec_un_pseudo_bien_trop_long_pour_tenir_sur_une_seule_ligne (pris)";
+    assert!(summary.len() > 75);
+    assert!(ics.contains(&format!("{}\r\n {}\r\n", &summary[..75], &summary[75..])));
+    assert!(ics.contains("CATEGORIES:drop,3c\r\n"));
+    assert!(ics.contains("STATUS:CANCELLED\r\nSEQUENCE:1\r\n"));
+    // alarmes seulement pour la fenêtre ouverte
+    assert_eq!(ics.matches("BEGIN:VALARM").count(), 2);
+    assert!(ics.contains("TRIGGER:-PT60M\r\n"));
+    assert!(ics.lines().all(|line| line.len() <= 75));
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, DATE};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+
+/// Mesures gardées pour l'estimation, les plus récentes.
+const SAMPLES: usize = 32;
+/// Décalage encore inconnu.
+const UNKNOWN: i64 = i64::MIN;
+
+/// Horloge du serveur, lue dans l'en-tête `Date` des réponses de l'API.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// Horodate les observations, et juge rappels, escalades et heures
+    /// calmes, avec l'heure corrigée du décalage mesuré, pour des fenêtres
+    /// justes même si l'horloge locale dérive.
+    pub corrected_timestamps: bool,
+}
+
+/// Décalage de l'horloge locale sur celle du serveur (serveur − local).
+///
+/// `Date` n'a qu'une précision d'une seconde : chaque mesure compare son
+/// milieu de seconde au milieu de l'aller-retour, et l'estimation est la
+/// médiane des [`SAMPLES`] dernières mesures. Les mesures vont dans un
+/// anneau sans verrou ; la médiane n'est recalculée qu'à la lecture, et
+/// seulement si une mesure est arrivée depuis.
+pub struct Clock {
+    corrected: AtomicBool,
+    skew_ms: AtomicI64,
+    samples: [AtomicI64; SAMPLES],
+    next: AtomicUsize,
+    stale: AtomicBool,
+}
+
+pub static CLOCK: Lazy<Clock> = Lazy::new(Clock::default);
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            corrected: AtomicBool::new(false),
+            skew_ms: AtomicI64::new(UNKNOWN),
+            samples: std::array::from_fn(|_| AtomicI64::new(UNKNOWN)),
+            next: AtomicUsize::new(0),
+            stale: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Clock {
+    pub fn configure(&self, config: &ClockConfig) {
+        self.corrected.store(config.corrected_timestamps, Ordering::Relaxed);
+    }
+
+    /// Mesure tirée d'une réponse reçue à `received` pour une requête
+    /// envoyée à `sent` ; ignorée sans en-tête `Date` lisible.
+    pub fn observe(&self, headers: &HeaderMap, sent: DateTime<Utc>, received: DateTime<Utc>) {
+        let server = headers
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok());
+        if let Some(server) = server {
+            self.record(server.with_timezone(&Utc), sent, received);
+        }
+    }
+
+    fn record(&self, server: DateTime<Utc>, sent: DateTime<Utc>, received: DateTime<Utc>) {
+        let local = sent + (received - sent) / 2;
+        let sample = (server + Duration::milliseconds(500) - local).num_milliseconds();
+        let slot = self.next.fetch_add(1, Ordering::Relaxed) % SAMPLES;
+        self.samples[slot].store(sample, Ordering::Relaxed);
+        self.stale.store(true, Ordering::Release);
+    }
+
+    /// Décalage estimé, en millisecondes ; positif si l'horloge locale retarde.
+    pub fn skew_ms(&self) -> Option<i64> {
+        if self.stale.swap(false, Ordering::Acquire) {
+            let mut sorted: Vec<i64> =
+                self.samples.iter().map(|s| s.load(Ordering::Relaxed)).filter(|&s| s != UNKNOWN).collect();
+            sorted.sort_unstable();
+            if let Some(&median) = sorted.get(sorted.len() / 2) {
+                self.skew_ms.store(median, Ordering::Relaxed);
+            }
+        }
+        match self.skew_ms.load(Ordering::Relaxed) {
+            UNKNOWN => None,
+            skew => Some(skew),
+        }
+    }
+
+    /// `at` (heure locale) corrigé du décalage si `corrected_timestamps` ;
+    /// sert aux observations comme aux décisions prises sur les fenêtres
+    /// (rappels, escalades, heures calmes).
+    pub fn corrected(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        match self.skew_ms() {
+            Some(skew) if self.corrected.load(Ordering::Relaxed) => at + Duration::milliseconds(skew),
+            _ => at,
+        }
+    }
+}
+
+#[test]
+fn test_clock_skew_estimate() {
+    use chrono::Timelike;
+    use reqwest::header::HeaderValue;
+
+    let clock = Clock::default();
+    let local = DateTime::parse_from_rfc3339("2025-07-14T19:00:00Z").unwrap().with_timezone(&Utc);
+    assert_eq!(clock.skew_ms(), None);
+    assert_eq!(clock.corrected(local), local);
+
+    let observe = |ahead_ms: i64, count: i64| {
+        for i in 0..count {
+            let sent = local + Duration::milliseconds(i * 1_370);
+            let received = sent + Duration::milliseconds(200);
+            let server = sent + Duration::milliseconds(100 + ahead_ms);
+            let mut headers = HeaderMap::new();
+            let date = server.with_nanosecond(0).unwrap().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            headers.insert(DATE, HeaderValue::from_str(&date).unwrap());
+            clock.observe(&headers, sent, received);
+        }
+    };
+
+    // serveur en retard de 60 s, puis mesures remplacées une fois l'anneau plein
+    observe(-60_000, 5);
+    assert!(clock.skew_ms().unwrap() < -59_000);
+
+    // serveur en avance de 3,2 s, aller-retour de 200 ms
+    observe(3_200, SAMPLES as i64);
+    clock.observe(&HeaderMap::new(), local, local);
+    let skew = clock.skew_ms().unwrap();
+    assert!((2_700..=3_700).contains(&skew), "{skew}");
+
+    // correction seulement si demandée
+    assert_eq!(clock.corrected(local), local);
+    clock.configure(&ClockConfig { corrected_timestamps: true });
+    assert_eq!(clock.corrected(local), local + Duration::milliseconds(skew));
+}
@@ -0,0 +1,231 @@
+use chrono_tz::Tz;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::calendar::CalendarConfig;
+use super::clock::ClockConfig;
+use super::control::ApiConfig;
+use super::escalation::EscalationConfig;
+use super::logging::LoggingConfig;
+use super::metrics::MetricsConfig;
+use super::notifiers::attachments::AttachmentConfig;
+use super::notifiers::digest::DigestConfig;
+use super::notifiers::quiet_hours::QuietHoursConfig;
+use super::notifiers::EventKind;
+use super::outbox::RetryPolicy;
+use super::reminders::ReminderConfig;
+use super::reports::ReportsConfig;
+use super::tags::{NameList, TagRule};
+use super::watchdog::WatchdogConfig;
+
+pub const CONFIG_PATH: &str = "config.json";
+
+/// Webhooks Discord des sinks par défaut `discord_logs` et `discord_drops`,
+/// lus dans l'environnement ; sans eux, ces sinks n'existent pas.
+const DISCORD_LOGS_URL_ENV: &str = "CLAIMER_DISCORD_LOGS_URL";
+const DISCORD_DROPS_URL_ENV: &str = "CLAIMER_DISCORD_DROPS_URL";
+
+/// Destination de notifications, identifiée par son nom dans `sinks`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    Discord { url: String },
+    /// POST JSON générique (voir `notifiers::webhook`).
+    Webhook { url: String },
+    Slack { url: String },
+    Email {
+        host: String,
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        from: String,
+        to: Vec<String>,
+        #[serde(default = "default_subject_prefix")]
+        subject_prefix: String,
+    },
+    Stdout,
+    File {
+        path: String,
+        /// Dossier des pièces jointes, `<path>_attachments` par défaut.
+        #[serde(default)]
+        attachments_dir: Option<String>,
+    },
+}
+
+impl SinkConfig {
+    /// Type du sink, utilisé pour choisir ses templates.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            SinkConfig::Discord { .. } => "discord",
+            SinkConfig::Webhook { .. } => "webhook",
+            SinkConfig::Slack { .. } => "slack",
+            SinkConfig::Email { .. } => "email",
+            SinkConfig::Stdout => "stdout",
+            SinkConfig::File { .. } => "file",
+        }
+    }
+}
+
+fn default_smtp_port() -> u16 {
+    25
+}
+
+fn default_subject_prefix() -> String {
+    "[claimer]".to_string()
+}
+
+/// Configuration chargée depuis `config.json` ; chaque champ absent prend sa
+/// valeur par défaut, un fichier absent donne la configuration par défaut.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Fuseaux d'affichage (noms IANA), le premier est le fuseau principal.
+    pub display_timezones: Vec<String>,
+    /// Sinks de notification par nom ; par défaut `discord_logs` et
+    /// `discord_drops`, si `CLAIMER_DISCORD_LOGS_URL` et
+    /// `CLAIMER_DISCORD_DROPS_URL` sont définies.
+    pub sinks: HashMap<String, SinkConfig>,
+    /// Type d'événement → noms des sinks destinataires.
+    pub routes: HashMap<EventKind, Vec<String>>,
+    /// Réessais de l'outbox de notifications.
+    pub outbox: RetryPolicy,
+    /// Rappels avant l'ouverture et à la fin des fenêtres.
+    pub reminders: ReminderConfig,
+    /// Dossier des templates de messages (voir `templates`).
+    pub templates_dir: String,
+    /// Pièces jointes générées (carte de drop, CSV de fenêtre).
+    pub attachments: AttachmentConfig,
+    /// Regroupement des notifications en résumés périodiques.
+    pub digest: DigestConfig,
+    /// Export `.ics` des fenêtres, tenu à jour par le scanner.
+    pub calendar: CalendarConfig,
+    /// Listes de pseudos suivies ; chaque pseudo porte le tag de sa liste.
+    pub lists: Vec<NameList>,
+    /// Tags explicites : pseudo → tags, en plus de ceux des listes.
+    pub name_tags: HashMap<String, Vec<String>>,
+    /// Routage par tag, en plus de `routes` (voir `tags::TagRule`).
+    pub tag_routes: Vec<TagRule>,
+    /// Plage horaire où les notifications non urgentes sont retenues.
+    pub quiet_hours: QuietHoursConfig,
+    /// Alertes répétées pour les fenêtres imminentes non prises en compte.
+    pub escalation: EscalationConfig,
+    /// Endpoint Prometheus `/metrics`.
+    pub metrics: MetricsConfig,
+    /// Format et niveaux des logs.
+    pub logging: LoggingConfig,
+    /// API HTTP locale d'état et de pilotage.
+    pub api: ApiConfig,
+    /// Alertes quand le scan cale, et avis de rétablissement.
+    pub watchdog: WatchdogConfig,
+    /// Bilans quotidiens et résumés quotidiens/hebdomadaires.
+    pub reports: ReportsConfig,
+    /// Horloge du serveur : décalage mesuré et horodatage corrigé.
+    pub clock: ClockConfig,
+    /// `display_timezones` analysés, au premier appel de [`Config::timezones`].
+    #[serde(skip)]
+    zones: OnceCell<Vec<Tz>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            display_timezones: vec!["Europe/Paris".to_string()],
+            sinks: [("discord_logs", DISCORD_LOGS_URL_ENV), ("discord_drops", DISCORD_DROPS_URL_ENV)]
+                .into_iter()
+                .filter_map(|(name, var)| {
+                    let url = std::env::var(var).ok().filter(|url| !url.is_empty())?;
+                    Some((name.to_string(), SinkConfig::Discord { url }))
+                })
+                .collect(),
+            routes: HashMap::from([
+                (EventKind::Checkpoint, vec!["discord_logs".to_string()]),
+                (EventKind::DropWindow, vec!["discord_drops".to_string()]),
+                (EventKind::Reminder, vec!["discord_drops".to_string()]),
+                (EventKind::WindowEnded, vec!["discord_drops".to_string()]),
+                (EventKind::Claimed, vec!["discord_drops".to_string()]),
+                (EventKind::Escalation, vec!["discord_drops".to_string()]),
+                (EventKind::Watchdog, vec!["discord_logs".to_string()]),
+                (EventKind::Report, vec!["discord_logs".to_string()]),
+            ]),
+            outbox: RetryPolicy::default(),
+            reminders: ReminderConfig::default(),
+            templates_dir: "templates".to_string(),
+            attachments: AttachmentConfig::default(),
+            digest: DigestConfig::default(),
+            calendar: CalendarConfig::default(),
+            lists: vec![NameList { path: "./names/3c.txt".to_string(), tags: Vec::new() }],
+            name_tags: HashMap::new(),
+            tag_routes: Vec::new(),
+            quiet_hours: QuietHoursConfig::default(),
+            escalation: EscalationConfig::default(),
+            metrics: MetricsConfig::default(),
+            logging: LoggingConfig::default(),
+            api: ApiConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            reports: ReportsConfig::default(),
+            clock: ClockConfig::default(),
+            zones: OnceCell::new(),
+        }
+    }
+}
+
+pub static CONFIG: Lazy<Config> = Lazy::new(|| Config::load(CONFIG_PATH));
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match serde_json::from_str(&content) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("⚠️ {} illisible ({}), configuration par défaut", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Fuseaux d'affichage valides ; les noms inconnus sont ignorés (et
+    /// signalés une seule fois, l'analyse n'étant faite qu'au premier appel).
+    pub fn timezones(&self) -> &[Tz] {
+        self.zones.get_or_init(|| {
+            let zones: Vec<Tz> = self
+                .display_timezones
+                .iter()
+                .filter_map(|name| match name.parse::<Tz>() {
+                    Ok(tz) => Some(tz),
+                    Err(_) => {
+                        eprintln!("⚠️ Fuseau horaire inconnu ignoré : {}", name);
+                        None
+                    }
+                })
+                .collect();
+            if zones.is_empty() {
+                vec![chrono_tz::Europe::Paris]
+            } else {
+                zones
+            }
+        })
+    }
+}
+
+#[test]
+fn test_config_defaults_and_timezones() {
+    let config: Config = serde_json::from_str("{}").unwrap();
+    assert_eq!(config.timezones(), vec![chrono_tz::Europe::Paris]);
+    assert_eq!(config.routes[&EventKind::DropWindow], vec!["discord_drops".to_string()]);
+
+    let config: Config = serde_json::from_str(
+        r#"{"sinks": {"mail": {"type": "email", "host": "localhost", "from": "a@b", "to": ["c@d"]}},
+            "routes": {"drop_window": ["mail"]}}"#,
+    )
+    .unwrap();
+    assert!(matches!(config.sinks["mail"], SinkConfig::Email { port: 25, .. }));
+
+    let config: Config =
+        serde_json::from_str(r#"{"display_timezones": ["America/New_York", "Nowhere/Land", "UTC"]}"#).unwrap();
+    assert_eq!(config.timezones(), vec![chrono_tz::America::New_York, chrono_tz::UTC]);
+}
@@ -0,0 +1,566 @@
+//! API HTTP locale d'état et de pilotage du scanner.
+//!
+//! Lecture :
+//! - `GET /stats` : photo des statistiques (voir `stats::StatsSnapshot`)
+//! - `GET /control` : pause, concurrence et nombre de pseudos suivis
+//! - `GET /names/{pseudo}` : état connu d'un pseudo (UUID, dernière
+//!   vérification, tags, quarantaine, dernière fenêtre)
+//! - `GET /windows?all=true&limit=100` : fenêtres ouvertes (ou toutes)
+//! - `GET /quarantine` : pseudos en quarantaine
+//! - `GET /outbox?status=pending&limit=50` : notifications de l'outbox
+//!
+//! Pilotage :
+//! - `POST /pause`, `POST /resume`
+//! - `PUT /concurrency` `{"concurrency": 500}` : requêtes simultanées maximum
+//! - `POST /names` `{"names": [...], "tags": [...]}` : ajout à la rotation
+//! - `DELETE /names/{pseudo}` : retrait de la rotation
+//! - `POST /windows/{pseudo}/ack` : comme la commande `ack`
+//! - `DELETE /quarantine/{pseudo}` : sortie de quarantaine, le pseudo
+//!   revient dans la rotation dès le batch suivant
+//!
+//! Les ajouts et retraits ne valent que jusqu'au redémarrage : les listes de
+//! `lists` restent la référence. L'API ne démarre que si `listen` et `token`
+//! sont configurés ; chaque requête doit porter `Authorization: Bearer
+//! <token>`, et celles dont l'en-tête `Host` ou `Origin` n'est pas local sont
+//! refusées (rebinding DNS, pages web ouvertes dans le navigateur).
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Notify, Semaphore};
+use tracing::{error, info, warn};
+
+use super::outbox::{Outbox, OutboxEntry, OutboxStatus};
+use super::quarantine::{Quarantine, QuarantineEntry};
+use super::sql_management::{insert_name, UsernameMap};
+use super::stats::{StatsSnapshot, STATS};
+use super::tags::TAGS;
+use super::username::Username;
+use super::window_store::{StoredWindow, WindowStore};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Adresse d'écoute (ex. `127.0.0.1:9899`), aucun serveur si absente.
+    pub listen: Option<String>,
+    /// Jeton attendu dans `Authorization: Bearer …` ; l'API ne démarre pas sans.
+    pub token: Option<String>,
+}
+
+/// État pilotable du scanner, partagé entre les workers et l'API : pause,
+/// limite de requêtes simultanées et rotation des pseudos.
+pub struct ScanControl {
+    paused: AtomicBool,
+    resumed: Notify,
+    semaphore: Arc<Semaphore>,
+    /// Nombre de permis visé ; les baisses sont absorbées au fil des requêtes.
+    concurrency: Mutex<usize>,
+    names: Arc<UsernameMap>,
+    /// Ordre de passage des pseudos, parcouru par les workers ; une seule
+    /// liste partagée, remplacée en entier quand l'API ajoute ou retire des
+    /// pseudos (rare) plutôt que copiée à chaque batch.
+    rotation: RwLock<Arc<[Username]>>,
+}
+
+impl ScanControl {
+    pub fn new(names: Arc<UsernameMap>, concurrency: usize) -> Self {
+        let rotation: Arc<[Username]> = names.iter().map(|e| e.key().clone()).collect();
+        Self {
+            paused: AtomicBool::new(false),
+            resumed: Notify::new(),
+            semaphore: Arc::new(Semaphore::new(concurrency)),
+            concurrency: Mutex::new(concurrency),
+            names,
+            rotation: RwLock::new(rotation),
+        }
+    }
+
+    pub fn semaphore(&self) -> &Semaphore {
+        &self.semaphore
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Renvoie `false` si le scanner était déjà en pause.
+    pub fn pause(&self) -> bool {
+        !self.paused.swap(true, Ordering::AcqRel)
+    }
+
+    /// Renvoie `false` si le scanner tournait déjà.
+    pub fn resume(&self) -> bool {
+        let was_paused = self.paused.swap(false, Ordering::AcqRel);
+        self.resumed.notify_waiters();
+        was_paused
+    }
+
+    /// Attend la reprise si le scanner est en pause.
+    pub async fn wait_while_paused(&self) {
+        loop {
+            // inscrit avant le test : une reprise entre les deux n'est pas perdue
+            let mut resumed = pin!(self.resumed.notified());
+            resumed.as_mut().enable();
+            if !self.is_paused() {
+                return;
+            }
+            resumed.await;
+        }
+    }
+
+    pub fn concurrency(&self) -> usize {
+        *self.concurrency.lock()
+    }
+
+    /// Change le nombre maximal de requêtes simultanées (au moins 1). Une
+    /// baisse retire les permis libres tout de suite, les autres à mesure que
+    /// les requêtes en cours se terminent ; à appeler depuis le runtime tokio.
+    pub fn set_concurrency(&self, target: usize) -> usize {
+        let target = target.clamp(1, u32::MAX as usize);
+        let mut current = self.concurrency.lock();
+        if target > *current {
+            self.semaphore.add_permits(target - *current);
+        } else if target < *current {
+            let excess = *current - target;
+            let pending = excess - self.semaphore.forget_permits(excess);
+            if pending > 0 {
+                let semaphore = self.semaphore.clone();
+                tokio::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many_owned(pending as u32).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        *current = target;
+        target
+    }
+
+    /// Requêtes en cours (permis pris sous la limite actuelle).
+    pub fn in_flight(&self) -> usize {
+        self.concurrency().saturating_sub(self.semaphore.available_permits())
+    }
+
+    pub fn names_len(&self) -> usize {
+        self.rotation.read().len()
+    }
+
+    /// `size` pseudos de la rotation à partir de `offset` (modulo sa taille).
+    pub fn batch(&self, offset: usize, size: usize) -> Vec<Username> {
+        let rotation = self.rotation.read().clone();
+        if rotation.is_empty() {
+            return Vec::new();
+        }
+        let start = offset % rotation.len();
+        let end = (start + size).min(rotation.len());
+        let rest = size.min(rotation.len()) - (end - start);
+        let mut batch = rotation[start..end].to_vec();
+        batch.extend_from_slice(&rotation[..rest]);
+        batch
+    }
+
+    /// Ajoute des pseudos à la rotation et leur donne `tags` ; renvoie ceux
+    /// qui n'étaient pas encore suivis.
+    pub fn add_names<S: AsRef<str>>(&self, raw: &[String], tags: &[S]) -> Vec<String> {
+        let tags = TAGS.set_of(tags);
+        let mut added = Vec::new();
+        for name in raw {
+            if insert_name(&self.names, name) {
+                added.push(Username::new(name));
+            }
+            if let Some(mut state) = self.names.get_mut(Username::key_of(name).as_str()) {
+                state.add_tags(tags);
+            }
+        }
+        if !added.is_empty() {
+            let mut rotation = self.rotation.write();
+            *rotation = rotation.iter().cloned().chain(added.iter().cloned()).collect();
+        }
+        added.iter().map(|name| name.display().into_owned()).collect()
+    }
+
+    /// Retire un pseudo du suivi ; `false` s'il n'était pas suivi.
+    pub fn remove_name(&self, raw: &str) -> bool {
+        let key = Username::key_of(raw);
+        if self.names.remove(key.as_str()).is_none() {
+            return false;
+        }
+        let mut rotation = self.rotation.write();
+        *rotation = rotation.iter().filter(|name| name.key() != key).cloned().collect();
+        true
+    }
+}
+
+/// Données servies par l'API.
+#[derive(Clone)]
+pub struct ApiState {
+    pub control: Arc<ScanControl>,
+    pub windows: Arc<WindowStore>,
+    pub quarantine: Arc<Quarantine>,
+    pub outbox: Option<Arc<Outbox>>,
+}
+
+/// Erreur renvoyée en `{"error": "..."}`.
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        error!(error = %e, "API : erreur de base de données");
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(e: std::io::Error) -> Self {
+        error!(error = %e, "API : erreur d'écriture");
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Serialize)]
+struct ControlState {
+    paused: bool,
+    concurrency: usize,
+    names_tracked: usize,
+}
+
+impl ControlState {
+    fn of(control: &ScanControl) -> Self {
+        Self { paused: control.is_paused(), concurrency: control.concurrency(), names_tracked: control.names_len() }
+    }
+}
+
+#[derive(Serialize)]
+struct NameInfo {
+    username: String,
+    tracked: bool,
+    uuid: Option<String>,
+    last_seen: Option<DateTime<Utc>>,
+    tags: Vec<String>,
+    quarantine: Option<QuarantineEntry>,
+    window: Option<StoredWindow>,
+}
+
+#[derive(Deserialize)]
+struct AddNames {
+    names: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SetConcurrency {
+    concurrency: usize,
+}
+
+#[derive(Deserialize)]
+struct WindowsQuery {
+    #[serde(default)]
+    all: bool,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct OutboxQuery {
+    status: Option<String>,
+    limit: Option<usize>,
+}
+
+async fn stats() -> Json<StatsSnapshot> {
+    Json(STATS.snapshot())
+}
+
+async fn control_state(State(state): State<ApiState>) -> Json<ControlState> {
+    Json(ControlState::of(&state.control))
+}
+
+async fn pause(State(state): State<ApiState>) -> Json<ControlState> {
+    if state.control.pause() {
+        info!("scanner mis en pause via l'API");
+    }
+    Json(ControlState::of(&state.control))
+}
+
+async fn resume(State(state): State<ApiState>) -> Json<ControlState> {
+    if state.control.resume() {
+        info!("scanner relancé via l'API");
+    }
+    Json(ControlState::of(&state.control))
+}
+
+async fn set_concurrency(State(state): State<ApiState>, Json(body): Json<SetConcurrency>) -> Json<ControlState> {
+    let concurrency = state.control.set_concurrency(body.concurrency);
+    info!(concurrency, "concurrence modifiée via l'API");
+    Json(ControlState::of(&state.control))
+}
+
+async fn name_info(State(state): State<ApiState>, Path(name): Path<String>) -> ApiResult<NameInfo> {
+    let key = Username::key_of(&name);
+    let mut info = NameInfo {
+        username: name.trim().to_string(),
+        tracked: false,
+        uuid: None,
+        last_seen: None,
+        tags: Vec::new(),
+        quarantine: state.quarantine.list().into_iter().find(|e| Username::key_of(&e.username) == key),
+        window: state.windows.latest(&name)?,
+    };
+    if let Some(entry) = state.control.names.get(key.as_str()) {
+        let known = entry.value();
+        info.username = known.canonical(entry.key()).unwrap_or_else(|| entry.key().display().into_owned());
+        info.tracked = true;
+        info.uuid = known.uuid();
+        info.last_seen = known.last_seen();
+        info.tags = TAGS.names(known.tags());
+    }
+    if !info.tracked && info.quarantine.is_none() && info.window.is_none() {
+        return Err(ApiError(StatusCode::NOT_FOUND, format!("pseudo inconnu : {}", info.username)));
+    }
+    Ok(Json(info))
+}
+
+async fn add_names(State(state): State<ApiState>, Json(body): Json<AddNames>) -> Json<serde_json::Value> {
+    let added = state.control.add_names(&body.names, &body.tags);
+    if !added.is_empty() {
+        info!(count = added.len(), "pseudos ajoutés via l'API");
+    }
+    Json(json!({ "added": added, "names_tracked": state.control.names_len() }))
+}
+
+async fn remove_name(State(state): State<ApiState>, Path(name): Path<String>) -> ApiResult<serde_json::Value> {
+    if !state.control.remove_name(&name) {
+        return Err(ApiError(StatusCode::NOT_FOUND, format!("pseudo non suivi : {}", name)));
+    }
+    info!(username = %name, "pseudo retiré via l'API");
+    Ok(Json(json!({ "removed": name, "names_tracked": state.control.names_len() })))
+}
+
+async fn windows(State(state): State<ApiState>, Query(query): Query<WindowsQuery>) -> ApiResult<Vec<StoredWindow>> {
+    let mut windows = match query.all {
+        true => state.windows.all(Some(query.limit.unwrap_or(100)))?,
+        false => state.windows.open_windows()?,
+    };
+    windows.sort_by_key(|w| w.window.begin);
+    Ok(Json(windows))
+}
+
+async fn ack(State(state): State<ApiState>, Path(name): Path<String>) -> ApiResult<serde_json::Value> {
+    let acknowledged = state.windows.acknowledge(&name)?;
+    Ok(Json(json!({ "username": name, "acknowledged": acknowledged })))
+}
+
+async fn quarantine(State(state): State<ApiState>) -> Json<Vec<QuarantineEntry>> {
+    Json(state.quarantine.list())
+}
+
+async fn release_quarantine(State(state): State<ApiState>, Path(name): Path<String>) -> ApiResult<serde_json::Value> {
+    let quarantine = state.quarantine.clone();
+    let released = {
+        let name = name.clone();
+        tokio::task::spawn_blocking(move || quarantine.release(&name)).await.map_err(std::io::Error::other)??
+    };
+    if !released {
+        return Err(ApiError(StatusCode::NOT_FOUND, format!("pseudo hors quarantaine : {}", name)));
+    }
+    info!(username = %name, "pseudo sorti de quarantaine via l'API");
+    Ok(Json(json!({ "released": name, "quarantine_total": state.quarantine.len() })))
+}
+
+async fn outbox(State(state): State<ApiState>, Query(query): Query<OutboxQuery>) -> ApiResult<Vec<OutboxEntry>> {
+    let Some(outbox) = &state.outbox else {
+        return Err(ApiError(StatusCode::NOT_FOUND, "outbox désactivée".to_string()));
+    };
+    let status = match query.status.as_deref() {
+        None => None,
+        Some(raw) => match OutboxStatus::parse(raw) {
+            Some(status) => Some(status),
+            None => return Err(ApiError(StatusCode::BAD_REQUEST, format!("statut inconnu : {}", raw))),
+        },
+    };
+    Ok(Json(outbox.list(status, query.limit.unwrap_or(50))?))
+}
+
+/// `localhost`, `127.0.0.1` ou `[::1]`, avec ou sans port.
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    name.eq_ignore_ascii_case("localhost") || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Comparaison en temps constant, pour ne pas révéler le jeton par la durée.
+fn same_token(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len() && given.iter().zip(expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Refuse les requêtes d'hôte ou d'origine non locale, puis celles sans le
+/// bon jeton.
+async fn authorize(State(token): State<Arc<str>>, headers: HeaderMap, request: Request, next: Next) -> Response {
+    let header_str = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let host_ok = header_str(header::HOST).is_some_and(is_local_host);
+    let origin_ok = header_str(header::ORIGIN).is_none_or(|origin| {
+        origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")).is_some_and(is_local_host)
+    });
+    if !host_ok || !origin_ok {
+        warn!(host = ?header_str(header::HOST), origin = ?header_str(header::ORIGIN), "API : requête non locale refusée");
+        return ApiError(StatusCode::FORBIDDEN, "hôte ou origine non locale".to_string()).into_response();
+    }
+    let given = header_str(header::AUTHORIZATION).and_then(|value| value.strip_prefix("Bearer ")).unwrap_or_default();
+    if !same_token(given.as_bytes(), token.as_bytes()) {
+        let mut response = ApiError(StatusCode::UNAUTHORIZED, "jeton manquant ou invalide".to_string()).into_response();
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+        return response;
+    }
+    next.run(request).await
+}
+
+/// Routes de l'API, accessibles avec `token`.
+pub fn router(state: ApiState, token: &str) -> Router {
+    Router::new()
+        .route("/stats", get(stats))
+        .route("/control", get(control_state))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .route("/concurrency", put(set_concurrency))
+        .route("/names", post(add_names))
+        .route("/names/{name}", get(name_info))
+        .route("/names/{name}", delete(remove_name))
+        .route("/windows", get(windows))
+        .route("/windows/{name}/ack", post(ack))
+        .route("/quarantine", get(quarantine))
+        .route("/quarantine/{name}", delete(release_quarantine))
+        .route("/outbox", get(outbox))
+        .route_layer(middleware::from_fn_with_state(Arc::<str>::from(token), authorize))
+        .with_state(state)
+}
+
+/// Lance l'API (sans effet si `listen` est absent).
+pub fn spawn_api_server(config: &ApiConfig, state: ApiState) {
+    let Some(listen) = config.listen.clone() else {
+        return;
+    };
+    let Some(token) = config.token.clone().filter(|token| !token.is_empty()) else {
+        error!(listen = %listen, "API de pilotage sans `token` configuré, non démarrée");
+        return;
+    };
+    if listen.parse::<SocketAddr>().is_ok_and(|addr| !addr.ip().is_loopback()) {
+        warn!(listen = %listen, "API de pilotage exposée hors de localhost, seules les requêtes d'hôte local passent");
+    }
+    tokio::spawn(async move {
+        match tokio::net::TcpListener::bind(&listen).await {
+            Ok(listener) => {
+                info!(listen = %listen, "API de pilotage démarrée");
+                if let Err(e) = axum::serve(listener, router(state, &token)).await {
+                    error!(error = %e, "API de pilotage arrêtée");
+                }
+            }
+            Err(e) => error!(listen = %listen, error = %e, "écoute de l'API de pilotage impossible"),
+        }
+    });
+}
+
+#[tokio::test]
+async fn test_control_api() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use super::sql_management::DropWindow;
+    use super::storage::open_memory_db;
+
+    let names = Arc::new(UsernameMap::default());
+    insert_name(&names, "Dream");
+    let control = Arc::new(ScanControl::new(names, 4));
+    let db = open_memory_db()?;
+    let windows = Arc::new(WindowStore::open(db.clone())?);
+    let now = Utc::now();
+    windows.insert("Dream", &DropWindow { begin: now, end: now + chrono::Duration::minutes(2) }, &[])?;
+    let quarantine_path = std::env::temp_dir().join(format!("control_test_{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&quarantine_path);
+    let quarantine = Arc::new(Quarantine::load(&quarantine_path)?);
+    quarantine.insert(&"Bad-Name".into(), "400 Bad Request")?;
+    let state = ApiState {
+        control: control.clone(),
+        windows,
+        quarantine: quarantine.clone(),
+        outbox: Some(Arc::new(Outbox::open(db, Default::default())?)),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let base = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, router(state, "secret")).await });
+
+    // jeton obligatoire, hôte et origine locaux seulement
+    let anonymous = reqwest::Client::new();
+    assert_eq!(anonymous.get(format!("{base}/control")).send().await?.status().as_u16(), 401);
+    let with_header = |name: &'static str, value: &'static str| {
+        anonymous.get(format!("{base}/control")).bearer_auth("secret").header(name, value).send()
+    };
+    assert_eq!(with_header("Origin", "https://evil.example").await?.status().as_u16(), 403);
+    assert_eq!(with_header("Host", "evil.example").await?.status().as_u16(), 403);
+    assert_eq!(with_header("Origin", "http://localhost:3000").await?.status().as_u16(), 200);
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(reqwest::header::AUTHORIZATION, "Bearer secret".parse()?);
+    let client = reqwest::Client::builder().default_headers(headers).build()?;
+
+    // pause : les workers attendent la reprise
+    client.post(format!("{base}/pause")).send().await?;
+    let waiting = tokio::spawn({
+        let control = control.clone();
+        async move { control.wait_while_paused().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!waiting.is_finished());
+    let state: serde_json::Value = client.post(format!("{base}/resume")).send().await?.json().await?;
+    assert_eq!(state["paused"], false);
+    tokio::time::timeout(std::time::Duration::from_secs(1), waiting).await??;
+
+    // concurrence : baisse appliquée aux permis libres
+    let state: serde_json::Value =
+        client.put(format!("{base}/concurrency")).json(&json!({ "concurrency": 2 })).send().await?.json().await?;
+    assert_eq!(state["concurrency"], 2);
+    assert_eq!(control.semaphore().available_permits(), 2);
+
+    // rotation des pseudos
+    let added: serde_json::Value =
+        client.post(format!("{base}/names")).json(&json!({ "names": ["Notch", "dream"], "tags": ["api"] })).send().await?.json().await?;
+    assert_eq!(added["added"], json!(["Notch"]));
+    assert_eq!(control.batch(1, 10).len(), 2);
+    let rotation = control.batch(0, 2);
+    assert_eq!(control.batch(1, 2), vec![rotation[1].clone(), rotation[0].clone()]);
+    let info: serde_json::Value = client.get(format!("{base}/names/NOTCH")).send().await?.json().await?;
+    assert_eq!(info["username"], "Notch");
+    assert_eq!(info["tags"], json!(["api"]));
+    assert_eq!(client.delete(format!("{base}/names/notch")).send().await?.status().as_u16(), 200);
+    assert_eq!(client.delete(format!("{base}/names/notch")).send().await?.status().as_u16(), 404);
+    assert_eq!(control.names_len(), 1);
+
+    // fenêtres et prise en compte
+    let open: serde_json::Value = client.get(format!("{base}/windows")).send().await?.json().await?;
+    assert_eq!(open[0]["username"], "Dream");
+    let acked: serde_json::Value = client.post(format!("{base}/windows/dream/ack")).send().await?.json().await?;
+    assert_eq!(acked["acknowledged"], 1);
+    assert_eq!(client.get(format!("{base}/outbox?status=nope")).send().await?.status().as_u16(), 400);
+
+    // sortie de quarantaine : effet immédiat sur l'ensemble partagé
+    assert_eq!(client.delete(format!("{base}/quarantine/bad-name")).send().await?.status().as_u16(), 200);
+    assert!(!quarantine.contains(&"Bad-Name".into()));
+    assert_eq!(client.delete(format!("{base}/quarantine/bad-name")).send().await?.status().as_u16(), 404);
+    let _ = std::fs::remove_file(&quarantine_path);
+    Ok(())
+}
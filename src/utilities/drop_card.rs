@@ -0,0 +1,179 @@
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use gif::{ColorOutput, DecodeOptions, Encoder, Frame, Repeat};
+use std::error::Error;
+
+/// Animation de fond et police de la carte de drop, embarquées dans le binaire.
+const BASE_GIF: &[u8] = include_bytes!("assets/naps.gif");
+const FONT: &[u8] = include_bytes!("assets/Starbim.ttf");
+
+const FONT_SIZE: f32 = 70.0;
+/// Position du haut du texte, depuis le bas de l'image.
+const TEXT_FROM_BOTTOM: i32 = 160;
+const MARGIN: f32 = 10.0;
+const SHADOW_OFFSET: i32 = 2;
+/// Vitesse NeuQuant (1 = meilleure qualité, 30 = plus rapide).
+const QUANT_SPEED: i32 = 10;
+
+/// Carte animée d'une alerte de drop : le pseudo, centré avec une ombre
+/// portée, sur chaque frame de `assets/naps.gif`. Renvoie le GIF encodé.
+///
+/// Travail CPU de quelques centaines de millisecondes : à appeler via
+/// `spawn_blocking` depuis le runtime.
+pub fn render_drop_card(username: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let font = FontRef::try_from_slice(FONT)?;
+
+    let mut options = DecodeOptions::new();
+    options.set_color_output(ColorOutput::RGBA);
+    let mut decoder = options.read_info(BASE_GIF)?;
+    let (width, height) = (decoder.width(), decoder.height());
+    let (w, h) = (width as usize, height as usize);
+
+    let mut output = Vec::new();
+    {
+        let mut encoder = Encoder::new(&mut output, width, height, &[])?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        // état composé de l'animation, transparent au départ
+        let mut canvas = vec![0u8; w * h * 4];
+        let text = TextLayout::new(&font, username, w as f32);
+        let y = (h as i32 - TEXT_FROM_BOTTOM).max(0) as f32;
+        let x = ((w as f32 - text.width) / 2.0).max(0.0);
+
+        while let Some(frame) = decoder.read_next_frame()? {
+            let saved = (frame.dispose == gif::DisposalMethod::Previous).then(|| canvas.clone());
+            let (left, top) = (frame.left as usize, frame.top as usize);
+            let (fw, fh) = (frame.width as usize, frame.height as usize);
+            for row in 0..fh.min(h.saturating_sub(top)) {
+                for col in 0..fw.min(w.saturating_sub(left)) {
+                    let src = (row * fw + col) * 4;
+                    if frame.buffer[src + 3] != 0 {
+                        let dst = ((top + row) * w + left + col) * 4;
+                        canvas[dst..dst + 4].copy_from_slice(&frame.buffer[src..src + 4]);
+                    }
+                }
+            }
+
+            // aplatissement sur fond blanc puis texte (ombre d'abord)
+            let mut pixels: Vec<u8> = canvas
+                .chunks_exact(4)
+                .flat_map(|p| {
+                    let a = p[3] as u32;
+                    let blend = |c: u8| ((c as u32 * a + 255 * (255 - a)) / 255) as u8;
+                    [blend(p[0]), blend(p[1]), blend(p[2]), 255]
+                })
+                .collect();
+            let shadow = (x + SHADOW_OFFSET as f32, y + SHADOW_OFFSET as f32);
+            text.draw(&font, &mut pixels, w, shadow, [0, 0, 0], 0.5);
+            text.draw(&font, &mut pixels, w, (x, y), [255, 255, 255], 1.0);
+
+            let mut out = Frame::from_rgba_speed(width, height, &mut pixels, QUANT_SPEED);
+            out.delay = frame.delay;
+            encoder.write_frame(&out)?;
+
+            match frame.dispose {
+                gif::DisposalMethod::Background => {
+                    for row in top..(top + fh).min(h) {
+                        let start = (row * w + left) * 4;
+                        let end = (row * w + (left + fw).min(w)) * 4;
+                        canvas[start..end].fill(0);
+                    }
+                }
+                gif::DisposalMethod::Previous => {
+                    if let Some(saved) = saved {
+                        canvas = saved;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Pseudo mis en page sur une ligne, réduit si besoin pour tenir dans l'image.
+struct TextLayout {
+    chars: Vec<char>,
+    scale: PxScale,
+    width: f32,
+}
+
+impl TextLayout {
+    fn new(font: &FontRef<'_>, text: &str, max_width: f32) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let natural = Self::measure(font, &chars, PxScale::from(FONT_SIZE));
+        let available = max_width - 2.0 * MARGIN;
+        let size = if natural > available && natural > 0.0 { FONT_SIZE * available / natural } else { FONT_SIZE };
+        let scale = PxScale::from(size);
+        Self { width: Self::measure(font, &chars, scale), chars, scale }
+    }
+
+    fn measure(font: &FontRef<'_>, chars: &[char], scale: PxScale) -> f32 {
+        let scaled = font.as_scaled(scale);
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in chars {
+            let id = scaled.glyph_id(*c);
+            if let Some(prev) = previous {
+                width += scaled.kern(prev, id);
+            }
+            width += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    }
+
+    /// Dessine le texte sur une image RGBA de largeur `w`, `(x, y)` étant le
+    /// coin haut-gauche de la ligne.
+    fn draw(&self, font: &FontRef<'_>, pixels: &mut [u8], w: usize, (x, y): (f32, f32), color: [u8; 3], opacity: f32) {
+        let h = pixels.len() / 4 / w;
+        let scaled = font.as_scaled(self.scale);
+        let baseline = y + scaled.ascent();
+        let mut caret = x;
+        let mut previous = None;
+        for c in &self.chars {
+            let id = scaled.glyph_id(*c);
+            if let Some(prev) = previous {
+                caret += scaled.kern(prev, id);
+            }
+            let glyph = id.with_scale_and_position(self.scale, point(caret, baseline));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+
+            let Some(outlined) = font.outline_glyph(glyph) else { continue };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px as usize >= w || py as usize >= h {
+                    return;
+                }
+                let alpha = (coverage * opacity).clamp(0.0, 1.0);
+                let i = (py as usize * w + px as usize) * 4;
+                for (channel, target) in pixels[i..i + 3].iter_mut().zip(color) {
+                    *channel = (*channel as f32 * (1.0 - alpha) + target as f32 * alpha).round() as u8;
+                }
+            });
+        }
+    }
+}
+
+#[test]
+fn test_drop_card_keeps_animation() {
+    let card = render_drop_card("Dream").unwrap();
+
+    let mut options = DecodeOptions::new();
+    options.set_color_output(ColorOutput::RGBA);
+    let mut source = options.clone().read_info(BASE_GIF).unwrap();
+    let mut rendered = options.read_info(card.as_slice()).unwrap();
+    assert_eq!((rendered.width(), rendered.height()), (source.width(), source.height()));
+
+    let mut frames = 0;
+    while let Some(frame) = source.read_next_frame().unwrap() {
+        let delay = frame.delay;
+        let out = rendered.read_next_frame().unwrap().expect("one output frame per input frame");
+        assert_eq!(out.delay, delay);
+        frames += 1;
+    }
+    assert!(frames > 1);
+    assert!(rendered.read_next_frame().unwrap().is_none());
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+use super::clock::CLOCK;
+use super::notifiers::{Dispatcher, Event};
+use super::window_store::WindowStore;
+
+/// Alertes répétées pour les fenêtres imminentes non prises en compte ;
+/// désactivées par défaut, `enabled = true` pour les activer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationConfig {
+    pub enabled: bool,
+    /// Une fenêtre est imminente à moins de `within_minutes` de son ouverture.
+    pub within_minutes: i64,
+    pub repeat_minutes: i64,
+    pub check_interval_secs: u64,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self { enabled: false, within_minutes: 60, repeat_minutes: 10, check_interval_secs: 30 }
+    }
+}
+
+/// Un tour de l'escalade : une alerte pour chaque fenêtre ouverte imminente
+/// (ou en cours) non prise en compte, répétée toutes les `repeat_minutes`
+/// jusqu'au `ack`, à la prise du pseudo ou à la fin de la fenêtre.
+pub fn due_escalations(store: &WindowStore, config: &EscalationConfig, now: DateTime<Utc>) -> rusqlite::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    for stored in store.open_windows()? {
+        let window = stored.window;
+        if stored.acknowledged_at.is_some() || now >= window.end || window.begin - now > Duration::minutes(config.within_minutes) {
+            continue;
+        }
+        if stored.escalated_at.is_some_and(|last| now - last < Duration::minutes(config.repeat_minutes.max(1))) {
+            continue;
+        }
+        events.push(Event::Escalation {
+            username: stored.username.clone(),
+            window,
+            tags: stored.tags.clone(),
+            repeat: stored.escalations + 1,
+        });
+        store.mark_escalated(&stored.username, &window, now)?;
+    }
+    Ok(events)
+}
+
+/// Lance l'escalade en tâche de fond (sans effet si désactivée).
+pub fn spawn_escalation_scheduler(store: Arc<WindowStore>, dispatcher: Arc<Dispatcher>, config: EscalationConfig) {
+    if !config.enabled {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            let due = {
+                let (store, config) = (store.clone(), config.clone());
+                tokio::task::spawn_blocking(move || due_escalations(&store, &config, CLOCK.corrected(Utc::now())))
+                    .await
+                    .expect("escalade interrompue")
+            };
+            match due {
+                Ok(events) => {
+                    for event in events {
+                        dispatcher.emit(event).await;
+                    }
+                }
+                Err(e) => error!(error = %e, "escalade"),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(config.check_interval_secs.max(1))).await;
+        }
+    });
+}
+
+#[test]
+fn test_escalation_repeats_until_ack() -> rusqlite::Result<()> {
+    use super::sql_management::DropWindow;
+
+    let store = WindowStore::open(super::storage::open_memory_db()?)?;
+    let config = EscalationConfig { enabled: true, ..Default::default() };
+    let now = Utc::now();
+    let soon = DropWindow { begin: now + Duration::minutes(30), end: now + Duration::minutes(33) };
+    let later = DropWindow { begin: now + Duration::hours(5), end: now + Duration::minutes(303) };
+    store.insert("Dream", &soon, &["3c".to_string()])?;
+    store.insert("Notch", &later, &[])?;
+
+    let events = due_escalations(&store, &config, now)?;
+    assert!(matches!(events.as_slice(), [Event::Escalation { username, repeat: 1, tags, .. }] if username == "Dream" && tags == &["3c"]));
+    assert!(due_escalations(&store, &config, now + Duration::minutes(5))?.is_empty());
+    let events = due_escalations(&store, &config, now + Duration::minutes(10))?;
+    assert!(matches!(events.as_slice(), [Event::Escalation { repeat: 2, .. }]));
+
+    assert_eq!(store.acknowledge("DREAM")?, 1);
+    assert!(due_escalations(&store, &config, now + Duration::minutes(20))?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::stats::Outcome;
+
+/// Échantillons gardés par classe, les plus récents.
+pub const SAMPLES_PER_CLASS: usize = 10;
+/// Longueur maximale d'un échantillon (corps de réponse ou chaîne d'erreurs).
+const SAMPLE_MAX_CHARS: usize = 500;
+
+/// Classe d'échec d'une requête ; les refus de l'API (400, 403, 429) sont
+/// comptés à part, comme résultats de requête.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// Délai dépassé : timeout du client HTTP ou borne autour de la requête.
+    Timeout,
+    /// Connexion au proxy ou à l'API impossible.
+    Connect,
+    Tls,
+    /// Autre erreur de transport (connexion coupée, réponse illisible…).
+    Network,
+    /// Statut 5xx.
+    ServerError,
+    /// Statut inattendu hors 5xx (3xx, 401, 404…).
+    UnexpectedStatus,
+    /// Réponse 200 qui n'est pas la liste de profils attendue.
+    Decode,
+    /// Réponse 200 sans aucun profil pour un batch de plusieurs pseudos.
+    EmptyResult,
+}
+
+impl ErrorClass {
+    pub const ALL: [ErrorClass; 8] = [
+        ErrorClass::Timeout,
+        ErrorClass::Connect,
+        ErrorClass::Tls,
+        ErrorClass::Network,
+        ErrorClass::ServerError,
+        ErrorClass::UnexpectedStatus,
+        ErrorClass::Decode,
+        ErrorClass::EmptyResult,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Connect => "connect",
+            ErrorClass::Tls => "tls",
+            ErrorClass::Network => "network",
+            ErrorClass::ServerError => "server_error",
+            ErrorClass::UnexpectedStatus => "unexpected_status",
+            ErrorClass::Decode => "decode",
+            ErrorClass::EmptyResult => "empty_result",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorClass::Timeout => "délai dépassé",
+            ErrorClass::Connect => "connexion",
+            ErrorClass::Tls => "TLS",
+            ErrorClass::Network => "réseau",
+            ErrorClass::ServerError => "erreur serveur (5xx)",
+            ErrorClass::UnexpectedStatus => "statut inattendu",
+            ErrorClass::Decode => "réponse illisible",
+            ErrorClass::EmptyResult => "réponse vide",
+        }
+    }
+
+    /// Résultat de requête correspondant, pour les compteurs de [`Outcome`].
+    pub fn outcome(self) -> Outcome {
+        match self {
+            ErrorClass::Timeout => Outcome::Timeout,
+            _ => Outcome::Error,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Échec d'une requête, avec le corps de la réponse ou la chaîne d'erreurs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub class: ErrorClass,
+    pub detail: String,
+}
+
+impl Failure {
+    pub fn new(class: ErrorClass, detail: impl Into<String>) -> Self {
+        let mut detail = detail.into();
+        if let Some((cut, _)) = detail.char_indices().nth(SAMPLE_MAX_CHARS) {
+            detail.truncate(cut);
+            detail.push('…');
+        }
+        Self { class, detail }
+    }
+
+    /// Erreur de transport de reqwest ; le TLS se reconnaît au message des
+    /// erreurs sous-jacentes, reqwest ne l'exposant pas.
+    pub fn from_reqwest(e: &reqwest::Error) -> Self {
+        let detail = error_chain(e);
+        let lower = detail.to_ascii_lowercase();
+        let class = if e.is_timeout() {
+            ErrorClass::Timeout
+        } else if ["tls", "certificate", "handshake"].iter().any(|word| lower.contains(word)) {
+            ErrorClass::Tls
+        } else if e.is_connect() {
+            ErrorClass::Connect
+        } else if e.is_decode() {
+            ErrorClass::Decode
+        } else {
+            ErrorClass::Network
+        };
+        Self::new(class, detail)
+    }
+}
+
+/// `erreur : cause : cause…`
+fn error_chain(e: &(dyn Error + 'static)) -> String {
+    let mut chain = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        chain.push_str(" : ");
+        chain.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    chain
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureSample {
+    pub at: DateTime<Utc>,
+    pub detail: String,
+}
+
+/// Échecs d'une classe depuis le démarrage et derniers échantillons.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureStats {
+    pub class: ErrorClass,
+    pub count: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<FailureSample>,
+}
+
+/// Compteurs par classe et échantillons bornés, pour le débogage.
+pub struct Failures {
+    counts: [AtomicU64; ErrorClass::ALL.len()],
+    samples: Mutex<[VecDeque<FailureSample>; ErrorClass::ALL.len()]>,
+}
+
+impl Default for Failures {
+    fn default() -> Self {
+        Self { counts: std::array::from_fn(|_| AtomicU64::new(0)), samples: Mutex::new(std::array::from_fn(|_| VecDeque::new())) }
+    }
+}
+
+impl Failures {
+    pub fn record(&self, failure: &Failure, at: DateTime<Utc>) {
+        self.counts[failure.class.index()].fetch_add(1, Ordering::Relaxed);
+        let mut samples = self.samples.lock();
+        let samples = &mut samples[failure.class.index()];
+        if samples.len() == SAMPLES_PER_CLASS {
+            samples.pop_front();
+        }
+        samples.push_back(FailureSample { at, detail: failure.detail.clone() });
+    }
+
+    /// Toutes les classes, dans l'ordre de [`ErrorClass::ALL`].
+    pub fn snapshot(&self) -> Vec<FailureStats> {
+        let samples = self.samples.lock();
+        ErrorClass::ALL
+            .iter()
+            .map(|class| FailureStats {
+                class: *class,
+                count: self.counts[class.index()].load(Ordering::Relaxed),
+                samples: samples[class.index()].iter().cloned().collect(),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_failure_classes_and_samples() {
+    let failures = Failures::default();
+    let at = Utc::now();
+    for i in 0..15 {
+        failures.record(&Failure::new(ErrorClass::ServerError, format!("502 : bad gateway {i}")), at);
+    }
+    failures.record(&Failure::new(ErrorClass::Decode, "x".repeat(2_000)), at);
+
+    let snapshot = failures.snapshot();
+    assert_eq!(snapshot.len(), ErrorClass::ALL.len());
+    let server = snapshot.iter().find(|f| f.class == ErrorClass::ServerError).unwrap();
+    assert_eq!(server.count, 15);
+    assert_eq!(server.samples.len(), SAMPLES_PER_CLASS);
+    assert_eq!(server.samples[0].detail, "502 : bad gateway 5");
+    let decode = snapshot.iter().find(|f| f.class == ErrorClass::Decode).unwrap();
+    assert_eq!(decode.samples[0].detail.chars().count(), SAMPLE_MAX_CHARS + 1);
+    assert_eq!(snapshot.iter().find(|f| f.class == ErrorClass::Tls).unwrap().count, 0);
+    assert_eq!(ErrorClass::Timeout.outcome(), Outcome::Timeout);
+    assert_eq!(ErrorClass::EmptyResult.outcome(), Outcome::Error);
+}
@@ -0,0 +1,468 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+use super::quarantine::QuarantineEntry;
+use super::reports::{DailyRollup, Rollup};
+use super::templates::format_minutes;
+use super::time_display::format_in;
+use super::username::Username;
+use super::window_store::{StoredWindow, WindowStatus};
+
+/// Un pseudo est instable à partir de ce nombre de fenêtres de drop.
+pub const FLAPPING_MIN_WINDOWS: usize = 2;
+/// Pseudos affichés dans l'historique, les plus récemment libérés d'abord.
+const TIMELINE_MAX_NAMES: usize = 100;
+const PAST_MAX_WINDOWS: usize = 200;
+
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 200.0;
+const CHART_LEFT: f64 = 48.0;
+const CHART_BOTTOM: f64 = 24.0;
+const CHART_TOP: f64 = 10.0;
+const TIMELINE_HEIGHT: f64 = 18.0;
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em auto; max-width: 1000px; color: #1f2937; }
+h1 { margin-bottom: 0; }
+h2 { margin-top: 2em; border-bottom: 1px solid #e5e7eb; }
+.muted { color: #6b7280; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { text-align: left; padding: 4px 8px; border-bottom: 1px solid #f3f4f6; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+code { font-size: 0.95em; }
+.legend span { display: inline-block; margin-right: 1em; }
+.legend i { display: inline-block; width: 10px; height: 10px; margin-right: 4px; }
+.status-open { color: #b45309; }
+.status-claimed { color: #1d4ed8; }
+.status-closed { color: #6b7280; }
+";
+
+/// Contenu du rapport HTML (commande `report html`).
+pub struct HtmlReport<'a> {
+    /// Toutes les fenêtres connues.
+    pub windows: &'a [StoredWindow],
+    /// Bilans quotidiens du `from` au `to` inclus (les jours absents valent zéro).
+    pub rollups: &'a [DailyRollup],
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub quarantine: &'a [QuarantineEntry],
+}
+
+/// Page HTML autonome (styles et graphiques SVG en ligne, aucune ressource
+/// externe), dates affichées dans le fuseau `tz`.
+pub fn render_html(report: &HtmlReport<'_>, tz: Tz, now: DateTime<Utc>) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"fr\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>claimer_rs_full · rapport</title>\n");
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    html.push_str("<h1>Rapport claimer_rs_full</h1>\n");
+    html.push_str(&format!("<p class=\"muted\">Généré le {} ({})</p>\n", escape(&format_in(now, tz)), tz.name()));
+
+    let (upcoming, past): (Vec<&StoredWindow>, Vec<&StoredWindow>) =
+        report.windows.iter().partition(|w| w.status == WindowStatus::Open && w.window.end > now);
+    html.push_str(&upcoming_section(upcoming, tz, now));
+    html.push_str(&past_section(past, tz));
+    html.push_str(&stats_section(report));
+    html.push_str(&timeline_section(report.windows, tz, now));
+    html.push_str(&flapping_section(report.windows, tz));
+    html.push_str(&quarantine_section(report.quarantine, tz));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn upcoming_section(mut upcoming: Vec<&StoredWindow>, tz: Tz, now: DateTime<Utc>) -> String {
+    upcoming.sort_by_key(|w| w.window.begin);
+    let mut html = format!("<h2>Fenêtres à venir ({})</h2>\n", upcoming.len());
+    if upcoming.is_empty() {
+        html.push_str("<p class=\"muted\">Aucune fenêtre ouverte.</p>\n");
+        return html;
+    }
+    html.push_str("<table>\n<tr><th>Pseudo</th><th>Début</th><th>Fin</th><th>Ouverture</th><th>Tags</th></tr>\n");
+    for stored in upcoming {
+        let lead = match (stored.window.begin - now).num_minutes() {
+            minutes if minutes > 0 => format!("dans {}", format_minutes(minutes)),
+            _ => "en cours".to_string(),
+        };
+        html.push_str(&format!(
+            "<tr><td><b>{}</b></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&stored.username),
+            escape(&format_in(stored.window.begin, tz)),
+            escape(&format_in(stored.window.end, tz)),
+            lead,
+            escape(&stored.tags.join(", ")),
+        ));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn status_label(stored: &StoredWindow) -> &'static str {
+    match stored.status {
+        WindowStatus::Open => "ouverte",
+        WindowStatus::Claimed => "prise",
+        WindowStatus::Closed => "terminée",
+    }
+}
+
+fn status_class(status: WindowStatus) -> &'static str {
+    match status {
+        WindowStatus::Open => "status-open",
+        WindowStatus::Claimed => "status-claimed",
+        WindowStatus::Closed => "status-closed",
+    }
+}
+
+fn past_section(mut past: Vec<&StoredWindow>, tz: Tz) -> String {
+    past.sort_by_key(|w| std::cmp::Reverse(w.window.begin));
+    let mut html = format!("<h2>Fenêtres passées ({})</h2>\n", past.len());
+    if past.is_empty() {
+        html.push_str("<p class=\"muted\">Aucune fenêtre passée.</p>\n");
+        return html;
+    }
+    if past.len() > PAST_MAX_WINDOWS {
+        html.push_str(&format!("<p class=\"muted\">Les {} plus récentes.</p>\n", PAST_MAX_WINDOWS));
+    }
+    html.push_str("<table>\n<tr><th>Pseudo</th><th>Début</th><th>Fin</th><th>État</th><th>Prise / fin</th></tr>\n");
+    for stored in past.into_iter().take(PAST_MAX_WINDOWS) {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>\n",
+            escape(&stored.username),
+            escape(&format_in(stored.window.begin, tz)),
+            escape(&format_in(stored.window.end, tz)),
+            status_class(stored.status),
+            status_label(stored),
+            stored.closed_at.map(|at| escape(&format_in(at, tz))).unwrap_or_default(),
+        ));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// Une série d'un graphique en barres.
+struct Series<'a> {
+    name: &'a str,
+    color: &'a str,
+    values: Vec<f64>,
+}
+
+/// Graphique en barres SVG, empilées ou côte à côte, une colonne par libellé.
+fn bar_chart(labels: &[String], series: &[Series<'_>], stacked: bool, max: Option<f64>) -> String {
+    let columns = labels.len().max(1);
+    let plot_width = CHART_WIDTH - CHART_LEFT;
+    let plot_height = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+    let top = max.unwrap_or_else(|| {
+        (0..labels.len())
+            .map(|i| {
+                let values = series.iter().map(|s| s.values[i]);
+                if stacked { values.sum() } else { values.fold(0.0, f64::max) }
+            })
+            .fold(0.0, f64::max)
+    });
+    let top = if top > 0.0 { top } else { 1.0 };
+    let y = |value: f64| CHART_TOP + plot_height * (1.0 - value / top);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" width=\"100%\" role=\"img\">\n"
+    );
+    for fraction in [0.0, 0.5, 1.0] {
+        let value = top * fraction;
+        svg.push_str(&format!(
+            "<line x1=\"{CHART_LEFT}\" x2=\"{CHART_WIDTH}\" y1=\"{0:.1}\" y2=\"{0:.1}\" stroke=\"#e5e7eb\"/>\
+             <text x=\"{1}\" y=\"{2:.1}\" font-size=\"10\" text-anchor=\"end\" fill=\"#6b7280\">{3}</text>\n",
+            y(value),
+            CHART_LEFT - 4.0,
+            y(value) + 3.0,
+            short_number(value),
+        ));
+    }
+
+    let slot = plot_width / columns as f64;
+    let bar = slot * 0.7 / if stacked { 1.0 } else { series.len().max(1) as f64 };
+    // une quinzaine de libellés au plus sur l'axe
+    let every = columns.div_ceil(15);
+    for (i, label) in labels.iter().enumerate() {
+        let x = CHART_LEFT + slot * i as f64 + slot * 0.15;
+        let mut base = 0.0;
+        for (j, s) in series.iter().enumerate() {
+            let value = s.values[i];
+            let (bar_x, bottom) = if stacked { (x, base) } else { (x + bar * j as f64, 0.0) };
+            if value > 0.0 {
+                svg.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{} · {} : {}</title></rect>\n",
+                    bar_x,
+                    y(bottom + value),
+                    bar,
+                    y(bottom) - y(bottom + value),
+                    s.color,
+                    escape(label),
+                    escape(s.name),
+                    short_number(value),
+                ));
+            }
+            base += value;
+        }
+        if i % every == 0 {
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\" fill=\"#6b7280\">{}</text>\n",
+                CHART_LEFT + slot * (i as f64 + 0.5),
+                CHART_HEIGHT - 8.0,
+                escape(label.get(5..).unwrap_or(label)),
+            ));
+        }
+    }
+    svg.push_str("</svg>\n<div class=\"legend\">");
+    for s in series {
+        svg.push_str(&format!("<span><i style=\"background:{}\"></i>{}</span>", s.color, escape(s.name)));
+    }
+    svg.push_str("</div>\n");
+    svg
+}
+
+/// `1234567` → `1.2M`, `1500` → `1.5k`, `12.5` → `12.5`.
+fn short_number(value: f64) -> String {
+    match value {
+        v if v >= 1e6 => format!("{:.1}M", v / 1e6),
+        v if v >= 1e3 => format!("{:.1}k", v / 1e3),
+        v if v.fract() == 0.0 => format!("{}", v),
+        v => format!("{:.1}", v),
+    }
+}
+
+fn stats_section(report: &HtmlReport<'_>) -> String {
+    let mut html = format!("<h2>Statistiques quotidiennes du {} au {}</h2>\n", report.from, report.to);
+    if report.rollups.is_empty() {
+        html.push_str("<p class=\"muted\">Aucun bilan enregistré sur la période.</p>\n");
+        return html;
+    }
+    let by_day: HashMap<NaiveDate, &Rollup> = report.rollups.iter().map(|d| (d.day, &d.rollup)).collect();
+    let empty = Rollup::default();
+    let days: Vec<(String, &Rollup)> = report
+        .from
+        .iter_days()
+        .take_while(|day| *day <= report.to)
+        .map(|day| (day.to_string(), by_day.get(&day).copied().unwrap_or(&empty)))
+        .collect();
+    let labels: Vec<String> = days.iter().map(|(label, _)| label.clone()).collect();
+    let values = |f: &dyn Fn(&Rollup) -> f64| days.iter().map(|(_, r)| f(r)).collect::<Vec<f64>>();
+
+    html.push_str("<h3>Requêtes par jour</h3>\n");
+    html.push_str(&bar_chart(
+        &labels,
+        &[
+            Series { name: "200", color: "#16a34a", values: values(&|r| r.counts.ok as f64) },
+            Series { name: "429", color: "#f59e0b", values: values(&|r| r.counts.rate_limited as f64) },
+            Series { name: "403", color: "#a855f7", values: values(&|r| r.counts.forbidden as f64) },
+            Series {
+                name: "autres",
+                color: "#dc2626",
+                values: values(&|r| (r.counts.bad_request + r.counts.errors + r.counts.timeouts) as f64),
+            },
+        ],
+        true,
+        None,
+    ));
+    html.push_str("<h3>Drops détectés et pseudos pris</h3>\n");
+    html.push_str(&bar_chart(
+        &labels,
+        &[
+            Series { name: "drops détectés", color: "#f59e0b", values: values(&|r| r.drops_detected as f64) },
+            Series { name: "pris", color: "#2563eb", values: values(&|r| r.claims_observed as f64) },
+        ],
+        false,
+        None,
+    ));
+    html.push_str("<h3>Couverture (% des pseudos vérifiés dans la journée)</h3>\n");
+    html.push_str(&bar_chart(
+        &labels,
+        &[Series { name: "couverture", color: "#0d9488", values: values(&|r| (r.coverage_pct() * 10.0).round() / 10.0) }],
+        false,
+        Some(100.0),
+    ));
+    html
+}
+
+/// Fenêtres regroupées par pseudo, dans l'ordre chronologique.
+fn by_name(windows: &[StoredWindow]) -> Vec<Vec<&StoredWindow>> {
+    let mut groups: HashMap<String, Vec<&StoredWindow>> = HashMap::new();
+    for stored in windows {
+        groups.entry(Username::key_of(&stored.username)).or_default().push(stored);
+    }
+    let mut groups: Vec<Vec<&StoredWindow>> = groups.into_values().collect();
+    for group in &mut groups {
+        group.sort_by_key(|w| w.window.begin);
+    }
+    // les plus récemment libérés d'abord
+    groups.sort_by_key(|group| std::cmp::Reverse(group.iter().map(|w| w.detected_at).max()));
+    groups
+}
+
+/// Frise d'un pseudo entre `start` et `end` : possédé, libéré (avant la
+/// fenêtre), fenêtre de drop, puis repris ou resté libre.
+fn timeline(windows: &[&StoredWindow], start: DateTime<Utc>, end: DateTime<Utc>, now: DateTime<Utc>, tz: Tz) -> String {
+    let span = (end - start).num_seconds().max(1) as f64;
+    let x = |at: DateTime<Utc>| ((at - start).num_seconds() as f64 / span).clamp(0.0, 1.0) * CHART_WIDTH;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {CHART_WIDTH} {TIMELINE_HEIGHT}\" width=\"100%\" height=\"{TIMELINE_HEIGHT}\">\n\
+         <rect width=\"{CHART_WIDTH}\" height=\"{TIMELINE_HEIGHT}\" fill=\"#f9fafb\"/>\n"
+    );
+    // `min` : largeur minimale, pour qu'une fenêtre de 90 s reste visible
+    let mut segment = |from: DateTime<Utc>, to: DateTime<Utc>, color: &str, label: &str, min: f64| {
+        if to > from {
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" width=\"{:.1}\" height=\"{TIMELINE_HEIGHT}\" fill=\"{}\"><title>{} : {} → {}</title></rect>\n",
+                x(from).min(CHART_WIDTH - min),
+                (x(to) - x(from)).max(min),
+                color,
+                label,
+                escape(&format_in(from, tz)),
+                escape(&format_in(to, tz)),
+            ));
+        }
+    };
+    if let Some(first) = windows.first() {
+        segment(start, first.detected_at, "#93c5fd", "possédé", 1.0);
+    }
+    for (i, stored) in windows.iter().enumerate() {
+        let next = windows.get(i + 1).map(|w| w.detected_at).unwrap_or(now);
+        segment(stored.detected_at, stored.window.begin, "#d1d5db", "libéré", 1.0);
+        match (stored.status, stored.closed_at) {
+            (WindowStatus::Claimed, Some(claimed)) => segment(claimed, next, "#3b82f6", "repris", 1.0),
+            _ => segment(stored.window.end, next, "#e5e7eb", "libre", 1.0),
+        }
+        // par-dessus le reste
+        segment(stored.window.begin, stored.window.end, "#f59e0b", "fenêtre de drop", 3.0);
+    }
+    svg.push_str(&format!("<line x1=\"{0:.1}\" x2=\"{0:.1}\" y1=\"0\" y2=\"{TIMELINE_HEIGHT}\" stroke=\"#111827\"/>\n", x(now)));
+    svg.push_str("</svg>");
+    svg
+}
+
+fn timeline_section(windows: &[StoredWindow], tz: Tz, now: DateTime<Utc>) -> String {
+    let groups = by_name(windows);
+    let mut html = format!("<h2>Historique par pseudo ({})</h2>\n", groups.len());
+    if groups.is_empty() {
+        html.push_str("<p class=\"muted\">Aucun pseudo libéré pour l'instant.</p>\n");
+        return html;
+    }
+    let shown = &groups[..groups.len().min(TIMELINE_MAX_NAMES)];
+    let first = shown.iter().flatten().map(|w| w.detected_at).min().unwrap_or(now);
+    let last = shown.iter().flatten().map(|w| w.window.end).max().unwrap_or(now).max(now);
+    // un peu de marge pour voir l'état avant la première libération
+    let start = first - (last - first) / 20 - Duration::hours(1);
+    let end = last + Duration::hours(1);
+    html.push_str(&format!(
+        "<p class=\"muted\">Du {} au {} ; trait noir : maintenant.</p>\n<div class=\"legend\">\
+         <span><i style=\"background:#93c5fd\"></i>possédé</span><span><i style=\"background:#d1d5db\"></i>libéré</span>\
+         <span><i style=\"background:#f59e0b\"></i>fenêtre de drop</span><span><i style=\"background:#3b82f6\"></i>repris</span>\
+         <span><i style=\"background:#e5e7eb\"></i>libre</span></div>\n<table>\n",
+        escape(&format_in(start, tz)),
+        escape(&format_in(end, tz)),
+    ));
+    for group in shown {
+        let latest = group.last().expect("groupe non vide");
+        html.push_str(&format!(
+            "<tr><td style=\"width:10em\">{}</td><td>{}</td></tr>\n",
+            escape(&latest.username),
+            timeline(group, start, end, now, tz),
+        ));
+    }
+    html.push_str("</table>\n");
+    if groups.len() > shown.len() {
+        html.push_str(&format!("<p class=\"muted\">{} autres pseudos non affichés.</p>\n", groups.len() - shown.len()));
+    }
+    html
+}
+
+fn flapping_section(windows: &[StoredWindow], tz: Tz) -> String {
+    let flapping: Vec<Vec<&StoredWindow>> =
+        by_name(windows).into_iter().filter(|group| group.len() >= FLAPPING_MIN_WINDOWS).collect();
+    let mut html = format!("<h2>Pseudos instables ({})</h2>\n", flapping.len());
+    html.push_str(&format!(
+        "<p class=\"muted\">Pseudos libérés au moins {} fois.</p>\n",
+        FLAPPING_MIN_WINDOWS
+    ));
+    if flapping.is_empty() {
+        return html;
+    }
+    html.push_str("<table>\n<tr><th>Pseudo</th><th>Libérations</th><th>Reprises</th><th>Dernière libération</th></tr>\n");
+    for group in flapping {
+        let latest = group.last().expect("groupe non vide");
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td></tr>\n",
+            escape(&latest.username),
+            group.len(),
+            group.iter().filter(|w| w.status == WindowStatus::Claimed).count(),
+            escape(&format_in(latest.detected_at, tz)),
+        ));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn quarantine_section(quarantine: &[QuarantineEntry], tz: Tz) -> String {
+    let mut html = format!("<h2>Quarantaine ({})</h2>\n", quarantine.len());
+    if quarantine.is_empty() {
+        html.push_str("<p class=\"muted\">Aucun pseudo en quarantaine.</p>\n");
+        return html;
+    }
+    html.push_str("<table>\n<tr><th>Pseudo</th><th>Depuis</th><th>Raison</th></tr>\n");
+    for entry in quarantine {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td><code>{}</code></td></tr>\n",
+            escape(&entry.username),
+            escape(&format_in(entry.since, tz)),
+            escape(&entry.reason),
+        ));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+#[test]
+fn test_render_html_report() {
+    use super::sql_management::DropWindow;
+    use super::stats::Counts;
+
+    let now = DateTime::parse_from_rfc3339("2025-07-14T19:00:00Z").unwrap().with_timezone(&Utc);
+    let stored = |name: &str, days_ago: i64, status: WindowStatus| {
+        let begin = now - Duration::days(days_ago);
+        StoredWindow {
+            username: name.to_string(),
+            window: DropWindow { begin, end: begin + Duration::seconds(90) },
+            status,
+            detected_at: begin - Duration::days(37),
+            closed_at: (status == WindowStatus::Claimed).then(|| begin + Duration::seconds(30)),
+            tags: vec!["3c".to_string()],
+            acknowledged_at: None,
+            escalated_at: None,
+            escalations: 0,
+        }
+    };
+    let windows = vec![
+        stored("Dream", -2, WindowStatus::Open),
+        stored("Dream", 60, WindowStatus::Claimed),
+        stored("jeb_", 3, WindowStatus::Closed),
+    ];
+    let day = NaiveDate::from_ymd_opt(2025, 7, 13).unwrap();
+    let rollups = [DailyRollup { day, rollup: Rollup { counts: Counts { ok: 90, errors: 10, ..Default::default() }, ..Default::default() } }];
+    let quarantine = [QuarantineEntry { username: "bad<name>".into(), reason: "400 \"invalid\"".into(), since: now }];
+    let report = HtmlReport { windows: &windows, rollups: &rollups, from: day - Duration::days(2), to: day + Duration::days(1), quarantine: &quarantine };
+
+    let html = render_html(&report, chrono_tz::Europe::Paris, now);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    // autonome : seul l'espace de noms SVG est une URL
+    assert_eq!(html.matches("http").count(), html.matches("xmlns=\"http://www.w3.org/2000/svg\"").count());
+    assert!(html.contains("<h2>Fenêtres à venir (1)</h2>"));
+    assert!(html.contains("<td><b>Dream</b></td>") && html.contains("dans 48h"));
+    assert!(html.contains("<h2>Fenêtres passées (2)</h2>"));
+    assert!(html.contains("<title>2025-07-13 · 200 : 90</title>"));
+    assert!(html.contains("<h2>Historique par pseudo (2)</h2>"));
+    assert!(html.contains("<title>repris : "));
+    assert!(html.contains("<h2>Pseudos instables (1)</h2>"));
+    assert!(html.contains("bad&lt;name&gt;") && html.contains("400 &quot;invalid&quot;"));
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Arc;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Lignes lisibles, pour un terminal.
+    Text,
+    /// Un objet JSON par ligne, pour journald / un collecteur de logs.
+    Json,
+}
+
+/// Journalisation (`tracing`) : les logs partent sur la sortie d'erreur, la
+/// sortie standard reste aux commandes de la CLI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// Niveaux par module, syntaxe `EnvFilter` (ex. `info,claimer_rs_full::utilities::requests=debug`) ;
+    /// la variable `RUST_LOG` a priorité.
+    pub filter: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { format: LogFormat::Text, filter: "info".to_string() }
+    }
+}
+
+/// Installe le subscriber global, vers la sortie d'erreur ; sans effet s'il
+/// y en a déjà un.
+pub fn init_logging(config: &LoggingConfig) {
+    install(config, std::io::stderr, true);
+}
+
+/// Comme [`init_logging`], mais les logs sont gardés en mémoire pour le
+/// tableau de bord (`tui`), qui occupe le terminal.
+pub fn init_logging_to(config: &LoggingConfig, buffer: LogBuffer) {
+    install(config, buffer, false);
+}
+
+fn install<W>(config: &LoggingConfig, writer: W, ansi: bool)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&config.filter))
+        .unwrap_or_else(|e| {
+            eprintln!("⚠️ Filtre de logs invalide ({}), « info » utilisé", e);
+            EnvFilter::new("info")
+        });
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_ansi(ansi).with_writer(writer);
+    let installed = match config.format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(false).try_init(),
+    };
+    if let Err(e) = installed {
+        eprintln!("⚠️ Journalisation déjà initialisée : {}", e);
+    }
+}
+
+/// Nombre de lignes gardées par [`LogBuffer`].
+const LOG_BUFFER_LINES: usize = 500;
+
+/// Dernières lignes de log, en mémoire.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl LogBuffer {
+    /// Les `count` dernières lignes, de la plus ancienne à la plus récente.
+    pub fn last(&self, count: usize) -> Vec<String> {
+        let lines = self.lines.lock();
+        lines.iter().skip(lines.len().saturating_sub(count)).cloned().collect()
+    }
+}
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        let mut lines = self.lines.lock();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            if lines.len() == LOG_BUFFER_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogBuffer;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
@@ -0,0 +1,314 @@
+use ahash::RandomState;
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use chrono::Utc;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::clock::CLOCK;
+use super::failures::FailureStats;
+use super::quarantine::Quarantine;
+use super::sql_management::UsernameMap;
+use super::stats::{Counts, Outcome, STATS};
+use super::window_store::WindowStore;
+
+/// Endpoint Prometheus `/metrics`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Adresse d'écoute (ex. `127.0.0.1:9898`), aucun serveur si absente.
+    pub listen: Option<String>,
+}
+
+/// Résultat d'un envoi de notification, étiquette `result`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendResult {
+    Sent,
+    Failed,
+    RateLimited,
+    Dead,
+}
+
+impl SendResult {
+    pub const ALL: [SendResult; 4] = [SendResult::Sent, SendResult::Failed, SendResult::RateLimited, SendResult::Dead];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SendResult::Sent => "sent",
+            SendResult::Failed => "failed",
+            SendResult::RateLimited => "rate_limited",
+            SendResult::Dead => "dead",
+        }
+    }
+}
+
+/// Envois de notifications par sink et résultat : un compteur par
+/// [`SendResult`] pour chaque sink, créés au premier envoi vers ce sink.
+#[derive(Default)]
+pub struct NotificationCounter {
+    sinks: DashMap<String, [AtomicU64; SendResult::ALL.len()], RandomState>,
+}
+
+impl NotificationCounter {
+    pub fn inc(&self, sink: &str, result: SendResult) {
+        // verrou en lecture dans le cas courant, écriture seulement à la création
+        if let Some(counters) = self.sinks.get(sink) {
+            counters[result as usize].fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.sinks.entry(sink.to_string()).or_default()[result as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, name: &str, help: &str, out: &mut String) {
+        header(out, name, help, "counter");
+        let mut sinks: Vec<(String, Vec<u64>)> = self
+            .sinks
+            .iter()
+            .map(|e| (label_value(e.key()), e.value().iter().map(|c| c.load(Ordering::Relaxed)).collect()))
+            .collect();
+        sinks.sort();
+        for (sink, values) in sinks {
+            for (result, value) in SendResult::ALL.iter().zip(values) {
+                let _ = writeln!(out, "{name}{{sink=\"{sink}\",result=\"{}\"}} {value}", result.as_str());
+            }
+        }
+    }
+}
+
+/// Histogramme à seaux fixes (bornes supérieures croissantes).
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Un compteur par borne, plus `+Inf`.
+    buckets: Vec<AtomicU64>,
+    /// Somme des observations, en bits de `f64`.
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self { bounds, buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(), sum: AtomicU64::new(0) }
+    }
+
+    pub fn observe(&self, value: f64) {
+        let i = self.bounds.iter().position(|bound| value <= *bound).unwrap_or(self.bounds.len());
+        self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| Some((f64::from_bits(bits) + value).to_bits()));
+    }
+
+    fn render(&self, name: &str, help: &str, out: &mut String) {
+        header(out, name, help, "histogram");
+        let mut cumulative = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = self.bounds.get(i).map_or("+Inf".to_string(), |b| b.to_string());
+            let _ = writeln!(out, "{name}_bucket{{le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_sum {}", f64::from_bits(self.sum.load(Ordering::Relaxed)));
+        let _ = writeln!(out, "{name}_count {cumulative}");
+    }
+}
+
+/// Métriques du scanner, alimentées par la boucle des batchs et les
+/// notifications ; les compteurs de requêtes et de batchs viennent de
+/// [`STATS`], les jauges (pseudos, fenêtres, fraîcheur) sont calculées au
+/// moment de la collecte.
+pub struct Metrics {
+    pub batch_duration: Histogram,
+    pub batch_retries: Histogram,
+    pub drops_detected: AtomicU64,
+    pub claims_detected: AtomicU64,
+    /// Envois de notifications par sink et résultat.
+    pub notifications: NotificationCounter,
+    /// Dernier batch réussi, en secondes Unix (0 : aucun).
+    pub last_success: AtomicI64,
+}
+
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            batch_duration: Histogram::new(&[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+            batch_retries: Histogram::new(&[0.0, 1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 30.0]),
+            drops_detected: AtomicU64::new(0),
+            claims_detected: AtomicU64::new(0),
+            notifications: NotificationCounter::default(),
+            last_success: AtomicI64::new(0),
+        }
+    }
+
+    /// Batch terminé après `attempts` requêtes en `seconds`.
+    pub fn record_batch(&self, seconds: f64, attempts: usize, success: bool) {
+        self.batch_duration.observe(seconds);
+        self.batch_retries.observe(attempts.saturating_sub(1) as f64);
+        if success {
+            self.last_success.store(Utc::now().timestamp(), Ordering::Relaxed);
+        }
+    }
+
+    /// Texte d'exposition Prometheus (format 0.0.4) ; `lifetime` et
+    /// `failures` : totaux depuis le démarrage.
+    pub fn render(&self, lifetime: &Counts, failures: &[FailureStats], sources: Option<&MetricsSources>) -> String {
+        let mut out = String::new();
+        header(&mut out, "claimer_requests_total", "Requêtes à l'API Mojang par résultat.", "counter");
+        for outcome in Outcome::ALL {
+            let _ = writeln!(out, "claimer_requests_total{{outcome=\"{}\"}} {}", outcome.as_str(), lifetime.get(outcome));
+        }
+        header(&mut out, "claimer_request_failures_total", "Requêtes en échec par classe d'erreur.", "counter");
+        for failure in failures {
+            let _ = writeln!(out, "claimer_request_failures_total{{class=\"{}\"}} {}", failure.class.as_str(), failure.count);
+        }
+        self.batch_duration.render(
+            "claimer_batch_duration_seconds",
+            "Durée de traitement d'un batch, réessais compris.",
+            &mut out,
+        );
+        self.batch_retries.render("claimer_batch_retries", "Réessais nécessaires par batch.", &mut out);
+        header(&mut out, "claimer_batches_total", "Batchs terminés par résultat.", "counter");
+        let _ = writeln!(out, "claimer_batches_total{{result=\"ok\"}} {}", lifetime.batches_ok);
+        let _ = writeln!(out, "claimer_batches_total{{result=\"failed\"}} {}", lifetime.batches_failed);
+        total(&mut out, "claimer_names_checked_total", "Pseudos dont l'état a été obtenu.", lifetime.names_checked);
+        counter(&mut out, "claimer_drops_detected_total", "Fenêtres de drop détectées.", &self.drops_detected);
+        counter(&mut out, "claimer_claims_detected_total", "Pseudos repris pendant leur fenêtre.", &self.claims_detected);
+        self.notifications.render("claimer_notifications_total", "Envois de notifications par sink et résultat.", &mut out);
+
+        let now = Utc::now().timestamp();
+        let last = self.last_success.load(Ordering::Relaxed);
+        if last > 0 {
+            gauge(&mut out, "claimer_last_success_age_seconds", "Temps écoulé depuis le dernier batch réussi.", now - last);
+        }
+        if let Some(skew) = CLOCK.skew_ms() {
+            header(&mut out, "claimer_clock_skew_seconds", "Décalage de l'horloge locale sur celle du serveur (positif si elle retarde).", "gauge");
+            let _ = writeln!(out, "claimer_clock_skew_seconds {}", skew as f64 / 1000.0);
+        }
+        if let Some(sources) = sources {
+            sources.render(now, &mut out);
+        }
+        out
+    }
+}
+
+/// Données lues à la collecte.
+#[derive(Clone)]
+pub struct MetricsSources {
+    pub names: Arc<UsernameMap>,
+    pub windows: Arc<WindowStore>,
+    pub quarantine: Arc<Quarantine>,
+}
+
+impl MetricsSources {
+    fn render(&self, now: i64, out: &mut String) {
+        let mut never_checked = 0;
+        let mut oldest = now;
+        for entry in self.names.iter() {
+            match entry.value().last_seen() {
+                Some(seen) => oldest = oldest.min(seen.timestamp()),
+                None => never_checked += 1,
+            }
+        }
+        gauge(out, "claimer_names_tracked", "Pseudos suivis.", self.names.len() as i64);
+        gauge(out, "claimer_names_never_checked", "Pseudos jamais vérifiés depuis le démarrage.", never_checked);
+        gauge(
+            out,
+            "claimer_oldest_check_age_seconds",
+            "Ancienneté de la plus vieille vérification parmi les pseudos vérifiés.",
+            now - oldest,
+        );
+        gauge(out, "claimer_names_quarantined", "Pseudos en quarantaine.", self.quarantine.len() as i64);
+        match self.windows.open_windows() {
+            Ok(open) => gauge(out, "claimer_open_windows", "Fenêtres de drop ouvertes ou à venir.", open.len() as i64),
+            Err(e) => error!(error = %e, "métriques : lecture des fenêtres impossible"),
+        }
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: &AtomicU64) {
+    total(out, name, help, value.load(Ordering::Relaxed));
+}
+
+fn total(out: &mut String, name: &str, help: &str, value: u64) {
+    header(out, name, help, "counter");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: i64) {
+    header(out, name, help, "gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Valeur d'étiquette échappée.
+fn label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+async fn metrics_handler(State(sources): State<MetricsSources>) -> impl IntoResponse {
+    let body = tokio::task::spawn_blocking(move || {
+        let snapshot = STATS.snapshot();
+        METRICS.render(&snapshot.lifetime, &snapshot.failures, Some(&sources))
+    }).await.unwrap_or_default();
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], body)
+}
+
+/// Lance le serveur `/metrics` (sans effet si `listen` est absent).
+pub fn spawn_metrics_server(config: &MetricsConfig, sources: MetricsSources) {
+    let Some(listen) = config.listen.clone() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let app = Router::new().route("/metrics", get(metrics_handler)).with_state(sources);
+        match tokio::net::TcpListener::bind(&listen).await {
+            Ok(listener) => {
+                info!(listen = %listen, "métriques sur /metrics");
+                if let Err(e) = axum::serve(listener, app).await {
+                    error!(error = %e, "serveur de métriques arrêté");
+                }
+            }
+            Err(e) => error!(listen = %listen, error = %e, "écoute du serveur de métriques impossible"),
+        }
+    });
+}
+
+#[test]
+fn test_metrics_exposition() {
+    use super::failures::{ErrorClass, Failure, Failures};
+
+    let metrics = Metrics::new();
+    let lifetime = Counts { ok: 2, rate_limited: 3, batches_ok: 1, batches_failed: 1, names_checked: 10, ..Default::default() };
+    metrics.record_batch(0.3, 1, true);
+    metrics.record_batch(12.0, 4, false);
+    metrics.notifications.inc("discord_drops", SendResult::Sent);
+
+    let failures = Failures::default();
+    failures.record(&Failure::new(ErrorClass::ServerError, "502 : bad gateway"), Utc::now());
+
+    let text = metrics.render(&lifetime, &failures.snapshot(), None);
+    assert!(text.contains("# TYPE claimer_requests_total counter\n"));
+    assert!(text.contains("claimer_requests_total{outcome=\"ok\"} 2\n"));
+    assert!(text.contains("claimer_requests_total{outcome=\"rate_limited\"} 3\n"));
+    assert!(text.contains("claimer_batch_duration_seconds_bucket{le=\"0.25\"} 0\n"));
+    assert!(text.contains("claimer_batch_duration_seconds_bucket{le=\"0.5\"} 1\n"));
+    assert!(text.contains("claimer_batch_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+    assert!(text.contains("claimer_batch_duration_seconds_sum 12.3\n"));
+    assert!(text.contains("claimer_batch_retries_bucket{le=\"3\"} 2\n"));
+    assert!(text.contains("claimer_requests_total{outcome=\"timeout\"} 0\n"));
+    assert!(text.contains("claimer_request_failures_total{class=\"server_error\"} 1\n"));
+    assert!(text.contains("claimer_request_failures_total{class=\"tls\"} 0\n"));
+    assert!(text.contains("claimer_batches_total{result=\"ok\"} 1\n"));
+    assert!(text.contains("claimer_batches_total{result=\"failed\"} 1\n"));
+    assert!(!text.contains("claimer_batches_failed_total"));
+    assert!(text.contains("claimer_names_checked_total 10\n"));
+    assert!(text.contains("claimer_notifications_total{sink=\"discord_drops\",result=\"sent\"} 1\n"));
+    assert!(text.contains("claimer_notifications_total{sink=\"discord_drops\",result=\"dead\"} 0\n"));
+    assert!(text.contains("claimer_last_success_age_seconds "));
+}
@@ -1,4 +1,27 @@
 pub mod sql_management;
-pub mod log_and_errors;
 pub mod requests;
-pub mod proxy_management;
\ No newline at end of file
+pub mod proxy_management;
+pub mod quarantine;
+pub mod username;
+pub mod config;
+pub mod time_display;
+pub mod notifiers;
+pub mod storage;
+pub mod outbox;
+pub mod window_store;
+pub mod reminders;
+pub mod templates;
+pub mod drop_card;
+pub mod calendar;
+pub mod tags;
+pub mod escalation;
+pub mod metrics;
+pub mod logging;
+pub mod stats;
+pub mod control;
+pub mod watchdog;
+pub mod tui;
+pub mod reports;
+pub mod html_report;
+pub mod failures;
+pub mod clock;
@@ -0,0 +1,146 @@
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::Event;
+use crate::utilities::drop_card::render_drop_card;
+use crate::utilities::time_display::{window_csv_header, window_csv_row};
+
+/// Fichier joint à une notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    pub fn new(filename: &str, content_type: &str, data: Vec<u8>) -> Self {
+        Self { filename: filename.to_string(), content_type: content_type.to_string(), data }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+}
+
+/// Pièces jointes générées automatiquement pour les événements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentConfig {
+    /// Carte animée du pseudo sur les alertes de drop (voir `drop_card`).
+    pub drop_card: bool,
+    /// CSV de la fenêtre ouverte, dans les fuseaux d'affichage (désactivé par défaut).
+    pub window_csv: bool,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self { drop_card: true, window_csv: false }
+    }
+}
+
+impl AttachmentConfig {
+    /// L'événement reçoit-il des pièces jointes générées ?
+    pub fn applies_to(&self, event: &Event) -> bool {
+        match event {
+            Event::DropWindow { .. } => self.drop_card || self.window_csv,
+            Event::Digest { events } => self.window_csv && events.iter().any(|e| matches!(e, Event::DropWindow { .. })),
+            _ => false,
+        }
+    }
+
+    /// Génère les pièces jointes de l'événement ; la carte est rendue hors du
+    /// runtime et simplement omise si son rendu échoue. Un résumé reçoit un
+    /// seul CSV avec toutes ses fenêtres ouvertes, sans cartes.
+    pub async fn build(&self, event: &Event, zones: &[Tz]) -> Vec<Attachment> {
+        if let Event::Digest { events } = event {
+            if !self.window_csv {
+                return Vec::new();
+            }
+            let mut csv = format!("{}\n", window_csv_header(zones));
+            for held in events {
+                if let Event::DropWindow { username, window, .. } = held {
+                    csv.push_str(&window_csv_row(username, window, zones));
+                    csv.push('\n');
+                }
+            }
+            let filename = format!("windows_{}.csv", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+            return vec![Attachment::new(&filename, "text/csv", csv.into_bytes())];
+        }
+        let Event::DropWindow { username, window, .. } = event else {
+            return Vec::new();
+        };
+        let stem = username.to_lowercase();
+        let mut attachments = Vec::new();
+
+        if self.drop_card {
+            let name = username.clone();
+            match tokio::task::spawn_blocking(move || render_drop_card(&name)).await.unwrap_or_else(|e| Err(e.into())) {
+                Ok(gif) => attachments.push(Attachment::new(&format!("{}.gif", stem), "image/gif", gif)),
+                Err(e) => warn!(username = %username, error = %e, "carte de drop non générée"),
+            }
+        }
+        if self.window_csv {
+            let csv = format!("{}\n{}\n", window_csv_header(zones), window_csv_row(username, window, zones));
+            attachments.push(Attachment::new(&format!("{}_window.csv", stem), "text/csv", csv.into_bytes()));
+        }
+        attachments
+    }
+}
+
+/// Pièces jointes qui tiennent, dans l'ordre, dans les `limit` octets d'un
+/// envoi vers `sink` ; les autres sont signalées puis écartées.
+pub fn fit(sink: &str, limit: usize, attachments: &[Attachment]) -> Vec<Attachment> {
+    let mut total = 0usize;
+    let mut kept = Vec::new();
+    for attachment in attachments {
+        if total.saturating_add(attachment.len()) <= limit {
+            total += attachment.len();
+            kept.push(attachment.clone());
+        } else if limit == 0 {
+            info!(sink, filename = %attachment.filename, "sink sans pièces jointes, fichier non transmis");
+        } else {
+            warn!(
+                sink,
+                filename = %attachment.filename,
+                size = attachment.len(),
+                limit,
+                "pièce jointe au-delà de la limite du sink, non transmise"
+            );
+        }
+    }
+    kept
+}
+
+#[tokio::test]
+async fn test_drop_attachments_fit_sink_limits() {
+    use crate::utilities::sql_management::DropWindow;
+
+    let now = chrono::Utc::now();
+    let event = Event::DropWindow { username: "Dream".into(), window: DropWindow { begin: now, end: now }, tags: vec![] };
+    let config = AttachmentConfig { drop_card: false, window_csv: true };
+    assert!(config.applies_to(&event));
+    assert!(!config.applies_to(&Event::Checkpoint { stats: Default::default() }));
+
+    let attachments = config.build(&event, &[chrono_tz::UTC]).await;
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].filename, "dream_window.csv");
+    let csv = String::from_utf8(attachments[0].data.clone()).unwrap();
+    assert!(csv.starts_with("username,begin (UTC),end (UTC)"));
+    assert!(csv.lines().nth(1).unwrap().starts_with("Dream,"));
+
+    let big = Attachment::new("big.bin", "application/octet-stream", vec![0; 10_000]);
+    let all = vec![big, attachments[0].clone()];
+    assert_eq!(fit("discord", 0, &all), vec![]);
+    assert_eq!(fit("discord", attachments[0].len(), &all), vec![attachments[0].clone()]);
+    assert_eq!(fit("discord", usize::MAX, &all).len(), 2);
+}
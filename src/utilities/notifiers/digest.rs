@@ -0,0 +1,103 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::{Event, EventKind};
+use crate::utilities::username::Username;
+
+/// Mode résumé : les événements des types `kinds` sont regroupés pendant
+/// `interval_secs` puis envoyés en un seul message par sink.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub kinds: Vec<EventKind>,
+    /// Pseudos toujours notifiés immédiatement.
+    pub priority: Vec<String>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 60,
+            kinds: vec![EventKind::DropWindow, EventKind::Reminder, EventKind::WindowEnded, EventKind::Claimed],
+            priority: Vec::new(),
+        }
+    }
+}
+
+/// Événements en attente du prochain résumé, dédoublonnés par (type, pseudo).
+///
+/// Le tampon est en mémoire : un arrêt du scanner entre deux résumés perd les
+/// événements retenus (ils restent visibles via `windows` et la base).
+pub struct DigestBuffer {
+    config: DigestConfig,
+    priority: HashSet<String>,
+    pending: Mutex<Vec<Event>>,
+}
+
+impl DigestBuffer {
+    pub fn new(config: DigestConfig) -> Self {
+        let priority = config.priority.iter().map(|name| Username::key_of(name)).collect();
+        Self { config, priority, pending: Mutex::new(Vec::new()) }
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.interval_secs.max(1))
+    }
+
+    /// Retient l'événement pour le prochain résumé, ou le rend s'il doit
+    /// partir tout de suite (type non résumé ou pseudo prioritaire).
+    pub fn hold(&self, event: Event) -> Result<(), Event> {
+        if !self.config.kinds.contains(&event.kind()) {
+            return Err(event);
+        }
+        let key = event.username().map(Username::key_of);
+        if key.as_ref().is_some_and(|key| self.priority.contains(key)) {
+            return Err(event);
+        }
+
+        let mut pending = self.pending.lock();
+        // même type et même pseudo : le plus récent remplace l'ancien, à sa place
+        let duplicate = pending
+            .iter()
+            .position(|held| held.kind() == event.kind() && held.username().map(Username::key_of) == key);
+        match duplicate {
+            Some(i) => pending[i] = event,
+            None => pending.push(event),
+        }
+        Ok(())
+    }
+
+    /// Vide le tampon.
+    pub fn take(&self) -> Vec<Event> {
+        std::mem::take(&mut *self.pending.lock())
+    }
+}
+
+#[test]
+fn test_digest_dedup_and_priority() {
+    use crate::utilities::sql_management::DropWindow;
+
+    let now = chrono::Utc::now();
+    let window = DropWindow { begin: now, end: now };
+    let drop = |name: &str| Event::DropWindow { username: name.into(), window, tags: vec![] };
+    let buffer = DigestBuffer::new(DigestConfig { enabled: true, priority: vec!["Notch".into()], ..Default::default() });
+
+    assert!(buffer.hold(drop("Dream")).is_ok());
+    assert!(buffer.hold(drop("jeb_")).is_ok());
+    assert!(buffer.hold(drop("DREAM")).is_ok());
+    assert!(buffer.hold(Event::Claimed { username: "Dream".into(), window, tags: vec![] }).is_ok());
+    assert!(buffer.hold(drop("notch")).is_err());
+    assert!(buffer.hold(Event::Checkpoint { stats: Default::default() }).is_err());
+
+    let held = buffer.take();
+    let names: Vec<_> = held.iter().map(|e| (e.kind(), e.username().unwrap())).collect();
+    assert_eq!(
+        names,
+        vec![(EventKind::DropWindow, "DREAM"), (EventKind::DropWindow, "jeb_"), (EventKind::Claimed, "Dream")]
+    );
+    assert!(buffer.take().is_empty());
+}
@@ -0,0 +1,87 @@
+use chrono::Utc;
+use futures::future::BoxFuture;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use super::{check_response, Event, Notifier, NotifyError};
+use super::attachments::Attachment;
+use crate::utilities::templates::{Message, SinkTemplates};
+
+/// Limite d'envoi des webhooks Discord (serveur sans boost), toutes pièces
+/// jointes confondues.
+const ATTACHMENT_LIMIT: usize = 10 * 1024 * 1024;
+/// Longueurs maximales acceptées par Discord (en caractères).
+const CONTENT_LIMIT: usize = 2_000;
+const DESCRIPTION_LIMIT: usize = 4_096;
+
+/// Webhook Discord : un message avec titre devient un embed, sinon le corps
+/// est envoyé en texte brut. Les pièces jointes partent en multipart, la
+/// première image sert d'image à l'embed.
+pub struct DiscordNotifier {
+    client: Client,
+    url: String,
+    templates: SinkTemplates,
+}
+
+impl DiscordNotifier {
+    pub fn new(client: Client, url: &str, templates: SinkTemplates) -> Self {
+        Self { client, url: url.to_string(), templates }
+    }
+
+    pub fn payload(message: &Message) -> Value {
+        if message.title.is_empty() {
+            return json!({ "content": truncate(&message.body, CONTENT_LIMIT) });
+        }
+        let mut embed = json!({
+            "title": message.title,
+            "description": truncate(&message.body, DESCRIPTION_LIMIT),
+            "color": 7506394,
+            "timestamp": Utc::now().to_rfc3339()
+        });
+        if !message.footer.is_empty() {
+            embed["footer"] = json!({ "text": message.footer });
+        }
+        json!({
+            "content": message.content,
+            "embeds": [embed],
+        })
+    }
+}
+
+/// Coupe un texte trop long (un gros résumé par exemple) en le signalant.
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max.saturating_sub(1)) {
+        Some((cut, _)) if text.chars().count() > max => format!("{}…", &text[..cut]),
+        _ => text.to_string(),
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn send<'a>(&'a self, event: &'a Event, attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+        Box::pin(async move {
+            let mut payload = Self::payload(&self.templates.render(event));
+            let request = if attachments.is_empty() {
+                self.client.post(&self.url).json(&payload)
+            } else {
+                if let (Some(embed), Some(image)) = (payload["embeds"].get_mut(0), attachments.iter().find(|a| a.is_image())) {
+                    embed["image"] = json!({ "url": format!("attachment://{}", image.filename) });
+                }
+                let mut form = Form::new().text("payload_json", payload.to_string());
+                for (i, attachment) in attachments.iter().enumerate() {
+                    let part = Part::bytes(attachment.data.clone())
+                        .file_name(attachment.filename.clone())
+                        .mime_str(&attachment.content_type)?;
+                    form = form.part(format!("files[{}]", i), part);
+                }
+                self.client.post(&self.url).multipart(form)
+            };
+            let response = request.send().await?;
+            check_response(response).await
+        })
+    }
+
+    fn attachment_limit(&self) -> usize {
+        ATTACHMENT_LIMIT
+    }
+}
@@ -0,0 +1,247 @@
+use base64::Engine;
+use chrono::Utc;
+use futures::future::BoxFuture;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+
+use super::attachments::Attachment;
+use super::{Event, Notifier, NotifyError};
+#[cfg(test)]
+use crate::utilities::templates::Templates;
+use crate::utilities::templates::SinkTemplates;
+
+/// Taille brute maximale des pièces jointes (≈ 27 Mo une fois en base64).
+const ATTACHMENT_LIMIT: usize = 20 * 1024 * 1024;
+
+/// Email via un relais SMTP local (sans TLS ni authentification, ex. postfix
+/// ou un relais de développement). Avec des pièces jointes, le message est
+/// en `multipart/mixed`, fichiers encodés en base64.
+pub struct EmailNotifier {
+    host: String,
+    port: u16,
+    from: String,
+    to: Vec<String>,
+    subject_prefix: String,
+    templates: SinkTemplates,
+}
+
+impl EmailNotifier {
+    pub fn new(
+        host: &str,
+        port: u16,
+        from: &str,
+        to: Vec<String>,
+        subject_prefix: &str,
+        templates: SinkTemplates,
+    ) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            from: from.to_string(),
+            to,
+            subject_prefix: subject_prefix.to_string(),
+            templates,
+        }
+    }
+
+    fn message(&self, event: &Event, attachments: &[Attachment]) -> String {
+        let rendered = self.templates.render(event);
+        let mut raw = format!(
+            "From: <{}>\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\n",
+            self.from,
+            self.to.iter().map(|to| format!("<{}>", to)).collect::<Vec<_>>().join(", "),
+            encode_header(&format!("{} {}", self.subject_prefix, rendered.title)),
+            Utc::now().to_rfc2822(),
+        );
+        if attachments.is_empty() {
+            raw.push_str("Content-Type: text/plain; charset=utf-8\r\n\r\n");
+            raw.push_str(&rendered.body);
+        } else {
+            let boundary = format!("claimer-{:x}", Utc::now().timestamp_micros());
+            raw.push_str(&format!("Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n", boundary));
+            raw.push_str(&format!(
+                "--{}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}\r\n",
+                boundary, rendered.body
+            ));
+            for attachment in attachments {
+                raw.push_str(&format!(
+                    "--{b}\r\nContent-Type: {ct}; name=\"{f}\"\r\nContent-Disposition: attachment; filename=\"{f}\"\r\nContent-Transfer-Encoding: base64\r\n\r\n",
+                    b = boundary,
+                    ct = attachment.content_type,
+                    f = attachment.filename,
+                ));
+                let encoded = base64::engine::general_purpose::STANDARD.encode(&attachment.data);
+                // lignes de 76 caractères au plus (RFC 2045)
+                for chunk in encoded.as_bytes().chunks(76) {
+                    raw.push_str(std::str::from_utf8(chunk).expect("base64 is ASCII"));
+                    raw.push_str("\r\n");
+                }
+            }
+            raw.push_str(&format!("--{}--", boundary));
+        }
+
+        let mut message = String::with_capacity(raw.len() + 64);
+        for line in raw.lines() {
+            // dot-stuffing (RFC 5321 §4.5.2)
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message
+    }
+
+    async fn deliver(&self, event: &Event, attachments: &[Attachment]) -> Result<(), NotifyError> {
+        let stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+
+        expect_reply(&mut reader, 220).await?;
+        command(&mut write, &mut reader, "EHLO claimer\r\n", 250).await?;
+        command(&mut write, &mut reader, &format!("MAIL FROM:<{}>\r\n", self.from), 250).await?;
+        for to in &self.to {
+            command(&mut write, &mut reader, &format!("RCPT TO:<{}>\r\n", to), 250).await?;
+        }
+        command(&mut write, &mut reader, "DATA\r\n", 354).await?;
+        write.write_all(self.message(event, attachments).as_bytes()).await?;
+        command(&mut write, &mut reader, ".\r\n", 250).await?;
+        command(&mut write, &mut reader, "QUIT\r\n", 221).await?;
+        Ok(())
+    }
+}
+
+/// Valeur d'en-tête non ASCII en mots encodés RFC 2047 (`=?UTF-8?B?…?=`),
+/// de 75 caractères au plus, repliés sur plusieurs lignes ; l'ASCII passe tel quel.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    // 45 octets → 60 caractères en base64, + 12 pour l'enrobage
+    let mut words = Vec::new();
+    let mut start = 0;
+    while start < value.len() {
+        let mut end = (start + 45).min(value.len());
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        words.push(format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(&value[start..end])));
+        start = end;
+    }
+    words.join("\r\n ")
+}
+
+async fn command<W, R>(write: &mut W, reader: &mut R, line: &str, expected: u16) -> Result<(), NotifyError>
+where
+    W: AsyncWriteExt + Unpin,
+    R: AsyncBufReadExt + Unpin,
+{
+    write.write_all(line.as_bytes()).await?;
+    expect_reply(reader, expected).await
+}
+
+/// Lit une réponse SMTP (éventuellement multi-lignes `250-…`) et vérifie son code.
+async fn expect_reply<R: AsyncBufReadExt + Unpin>(reader: &mut R, expected: u16) -> Result<(), NotifyError> {
+    let mut reply = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(NotifyError::Transport("connexion SMTP fermée".to_string()));
+        }
+        reply.push_str(&line);
+        // "250-…" annonce une suite, "250 …" termine la réponse
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+    let code: u16 = reply.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+    if code != expected {
+        return Err(NotifyError::Status { status: code, body: reply.trim_end().to_string() });
+    }
+    Ok(())
+}
+
+impl Notifier for EmailNotifier {
+    fn send<'a>(&'a self, event: &'a Event, attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+        Box::pin(async move {
+            timeout(Duration::from_secs(30), self.deliver(event, attachments))
+                .await
+                .map_err(|_| NotifyError::Transport("timeout SMTP".to_string()))?
+        })
+    }
+
+    fn attachment_limit(&self) -> usize {
+        ATTACHMENT_LIMIT
+    }
+}
+
+#[tokio::test]
+async fn test_email_against_local_smtp() {
+    use tokio::net::TcpListener;
+
+    // serveur SMTP minimal qui accepte tout et renvoie le contenu de DATA
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+        write.write_all(b"220 stand-in ESMTP\r\n").await.unwrap();
+        let mut data = String::new();
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            if in_data {
+                if line == ".\r\n" {
+                    in_data = false;
+                    write.write_all(b"250 queued\r\n").await.unwrap();
+                } else {
+                    data.push_str(&line);
+                }
+                continue;
+            }
+            let reply: &[u8] = match &line[..4] {
+                "EHLO" => b"250-stand-in\r\n250 8BITMIME\r\n",
+                "DATA" => {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                }
+                "QUIT" => {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"250 ok\r\n",
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        data
+    });
+
+    // template de test dont le corps contient une ligne commençant par un point
+    let dir = std::env::temp_dir().join(format!("email_templates_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("checkpoint.email.j2"), "{% block title %}Checkpoint{% endblock %}{% block body %}tout va bien\n.point{% endblock %}").unwrap();
+    let templates = Templates::load(&dir, vec![chrono_tz::UTC]);
+    let templates = SinkTemplates::new(std::sync::Arc::new(templates), "mail", "email");
+
+    let notifier = EmailNotifier::new("127.0.0.1", port, "bot@local", vec!["team@local".into()], "[claimer]", templates);
+    let event = Event::Checkpoint { stats: Default::default() };
+    let report = Attachment::new("report.csv", "text/csv", b"name,begin\nDream,0\n".to_vec());
+    notifier.send(&event, &[report]).await.expect("mail should be accepted");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let data = server.await.unwrap();
+    assert!(data.contains("Subject: [claimer] Checkpoint\r\n"));
+    assert!(data.contains("tout va bien\r\n..point\r\n"));
+    assert!(data.contains("Content-Disposition: attachment; filename=\"report.csv\"\r\n"));
+    assert!(data.contains("bmFtZSxiZWdpbgpEcmVhbSwwCg==\r\n"));
+
+    assert_eq!(encode_header("[claimer] Résumé hebdomadaire"), "=?UTF-8?B?W2NsYWltZXJdIFLDqXN1bcOpIGhlYmRvbWFkYWlyZQ==?=");
+    let long = encode_header(&"é".repeat(40));
+    assert!(long.split("\r\n ").all(|word| word.len() <= 75 && word.starts_with("=?UTF-8?B?")));
+    assert_eq!(long.split("\r\n ").count(), 2);
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+use super::attachments::Attachment;
+use super::{Event, Notifier, NotifyError};
+use crate::utilities::templates::SinkTemplates;
+
+/// Affiche les notifications dans la console.
+pub struct StdoutNotifier {
+    templates: SinkTemplates,
+}
+
+impl StdoutNotifier {
+    pub fn new(templates: SinkTemplates) -> Self {
+        Self { templates }
+    }
+}
+
+impl Notifier for StdoutNotifier {
+    fn send<'a>(&'a self, event: &'a Event, attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+        Box::pin(async move {
+            let message = self.templates.render(event);
+            println!("\n📣 {}\n{}", message.title, message.body);
+            for attachment in attachments {
+                println!("📎 {} ({} o)", attachment.filename, attachment.len());
+            }
+            Ok(())
+        })
+    }
+
+    /// Seuls les noms et tailles sont affichés.
+    fn attachment_limit(&self) -> usize {
+        usize::MAX
+    }
+}
+
+#[derive(Serialize)]
+struct FileLine<'a> {
+    at: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a Event,
+    /// Chemins des pièces jointes écrites à côté.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<String>,
+}
+
+/// Ajoute chaque notification en JSON lines dans un fichier ; les pièces
+/// jointes sont écrites dans `attachments_dir`, préfixées par l'instant
+/// d'envoi.
+pub struct FileNotifier {
+    path: PathBuf,
+    attachments_dir: PathBuf,
+}
+
+impl FileNotifier {
+    pub fn new(path: &str) -> Self {
+        Self { path: PathBuf::from(path), attachments_dir: PathBuf::from(format!("{}_attachments", path)) }
+    }
+
+    pub fn with_attachments_dir(mut self, dir: Option<&str>) -> Self {
+        if let Some(dir) = dir {
+            self.attachments_dir = PathBuf::from(dir);
+        }
+        self
+    }
+
+    async fn write_attachments(&self, at: DateTime<Utc>, attachments: &[Attachment]) -> std::io::Result<Vec<String>> {
+        if attachments.is_empty() {
+            return Ok(Vec::new());
+        }
+        tokio::fs::create_dir_all(&self.attachments_dir).await?;
+        let mut paths = Vec::new();
+        for attachment in attachments {
+            // le nom vient du pseudo : on neutralise tout séparateur de chemin
+            let name: String = attachment
+                .filename
+                .chars()
+                .map(|c| if c == '/' || c == '\\' { '_' } else { c })
+                .collect();
+            let path = self.attachments_dir.join(format!("{}_{}", at.format("%Y%m%dT%H%M%S%.3f"), name));
+            tokio::fs::write(&path, &attachment.data).await?;
+            paths.push(path.display().to_string());
+        }
+        Ok(paths)
+    }
+}
+
+impl Notifier for FileNotifier {
+    fn send<'a>(&'a self, event: &'a Event, attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+        Box::pin(async move {
+            let at = Utc::now();
+            let attachments = self.write_attachments(at, attachments).await?;
+            let mut line = serde_json::to_string(&FileLine { at, event, attachments })
+                .map_err(|e| NotifyError::Transport(e.to_string()))?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            // tokio::fs::File écrit en tâche de fond : flush pour garantir l'écriture
+            file.flush().await?;
+            Ok(())
+        })
+    }
+
+    fn attachment_limit(&self) -> usize {
+        usize::MAX
+    }
+}
@@ -0,0 +1,801 @@
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, BoxFuture};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+use super::clock::CLOCK;
+use super::config::{Config, SinkConfig};
+use super::metrics::{SendResult, METRICS};
+use super::outbox::{Outbox, OutboxEntry, OutboxStatus};
+use super::reports::Summary;
+use super::sql_management::DropWindow;
+use super::stats::StatsSnapshot;
+use super::tags::TagRule;
+use super::templates::{SinkTemplates, Templates};
+use super::watchdog::Problem;
+use attachments::{fit, Attachment, AttachmentConfig};
+use digest::DigestBuffer;
+use quiet_hours::QuietHoursConfig;
+
+pub mod attachments;
+pub mod digest;
+pub mod discord;
+pub mod quiet_hours;
+pub mod email;
+pub mod local;
+pub mod slack;
+pub mod webhook;
+
+/// Type d'événement, clé du routage vers les sinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Checkpoint,
+    DropWindow,
+    Reminder,
+    WindowEnded,
+    Claimed,
+    Digest,
+    Escalation,
+    Watchdog,
+    Report,
+}
+
+/// Statistiques d'un checkpoint : photo des compteurs du scanner (la fenêtre
+/// `last_1m` couvre l'intervalle entre deux checkpoints) et état de la
+/// quarantaine ; pourcentages et durées sont mis en forme au rendu des templates.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointStats {
+    #[serde(flatten)]
+    pub snapshot: Box<StatsSnapshot>,
+    pub quarantine_total: usize,
+    /// Pseudos mis en quarantaine depuis le checkpoint précédent.
+    pub quarantined: Vec<String>,
+}
+
+/// Événement émis par le scanner ; le reste du code ne sait pas où il part.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Checkpoint { stats: CheckpointStats },
+    DropWindow {
+        username: String,
+        window: DropWindow,
+        /// Tags du pseudo (listes d'origine, tags explicites), pour le routage.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    /// Rappel programmé avant l'ouverture d'une fenêtre.
+    Reminder {
+        username: String,
+        window: DropWindow,
+        minutes_before: i64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    WindowEnded {
+        username: String,
+        window: DropWindow,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    /// Un pseudo en fenêtre de drop a retrouvé un UUID.
+    Claimed {
+        username: String,
+        window: DropWindow,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    /// Résumé d'événements regroupés (voir `digest`), construit pour un sink.
+    Digest { events: Vec<Event> },
+    /// Fenêtre imminente pas encore prise en compte, répétée jusqu'au `ack`
+    /// (voir `escalation`) ; `repeat` compte les alertes, à partir de 1.
+    Escalation {
+        username: String,
+        window: DropWindow,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        repeat: u32,
+    },
+    /// Alerte du watchdog (voir `watchdog`) ; avec `recovered`, le problème
+    /// détecté à `since` a disparu.
+    Watchdog {
+        problem: Problem,
+        recovered: bool,
+        since: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        detail: String,
+    },
+    /// Résumé quotidien ou hebdomadaire (voir `reports`).
+    Report { summary: Box<Summary> },
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::Checkpoint { .. } => EventKind::Checkpoint,
+            Event::DropWindow { .. } => EventKind::DropWindow,
+            Event::Reminder { .. } => EventKind::Reminder,
+            Event::WindowEnded { .. } => EventKind::WindowEnded,
+            Event::Claimed { .. } => EventKind::Claimed,
+            Event::Digest { .. } => EventKind::Digest,
+            Event::Escalation { .. } => EventKind::Escalation,
+            Event::Watchdog { .. } => EventKind::Watchdog,
+            Event::Report { .. } => EventKind::Report,
+        }
+    }
+
+    /// Pseudo concerné, pour les événements liés à un pseudo.
+    pub fn username(&self) -> Option<&str> {
+        match self {
+            Event::Checkpoint { .. } | Event::Digest { .. } | Event::Watchdog { .. } | Event::Report { .. } => None,
+            Event::DropWindow { username, .. }
+            | Event::Reminder { username, .. }
+            | Event::WindowEnded { username, .. }
+            | Event::Claimed { username, .. }
+            | Event::Escalation { username, .. } => Some(username),
+        }
+    }
+
+    /// Fenêtre concernée, pour les événements liés à une fenêtre.
+    pub fn window(&self) -> Option<&DropWindow> {
+        match self {
+            Event::Checkpoint { .. } | Event::Digest { .. } | Event::Watchdog { .. } | Event::Report { .. } => None,
+            Event::DropWindow { window, .. }
+            | Event::Reminder { window, .. }
+            | Event::WindowEnded { window, .. }
+            | Event::Claimed { window, .. }
+            | Event::Escalation { window, .. } => Some(window),
+        }
+    }
+
+    /// Tags du pseudo concerné ; pour un résumé, ceux de tous ses événements.
+    pub fn tags(&self) -> Vec<&str> {
+        match self {
+            Event::Checkpoint { .. } | Event::Watchdog { .. } | Event::Report { .. } => Vec::new(),
+            Event::DropWindow { tags, .. }
+            | Event::Reminder { tags, .. }
+            | Event::WindowEnded { tags, .. }
+            | Event::Claimed { tags, .. }
+            | Event::Escalation { tags, .. } => tags.iter().map(String::as_str).collect(),
+            Event::Digest { events } => {
+                let mut all: Vec<&str> = Vec::new();
+                for tag in events.iter().flat_map(Event::tags) {
+                    if !all.contains(&tag) {
+                        all.push(tag);
+                    }
+                }
+                all
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NotifyError {
+    /// Connexion, TLS, timeout, écriture locale…
+    Transport(String),
+    /// Réponse HTTP ou SMTP non acceptée.
+    Status { status: u16, body: String },
+    /// 429 : le sink impose un délai avant le prochain envoi.
+    RateLimited { retry_after: Duration },
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Transport(e) => write!(f, "transport : {}", e),
+            NotifyError::Status { status, body } => write!(f, "status {} : {}", status, body),
+            NotifyError::RateLimited { retry_after } => write!(f, "429, réessayer dans {:.1}s", retry_after.as_secs_f64()),
+        }
+    }
+}
+
+impl std::error::Error for NotifyError {}
+
+impl From<reqwest::Error> for NotifyError {
+    fn from(e: reqwest::Error) -> Self {
+        NotifyError::Transport(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for NotifyError {
+    fn from(e: std::io::Error) -> Self {
+        NotifyError::Transport(e.to_string())
+    }
+}
+
+/// Vérifie qu'une réponse HTTP est un succès (2xx) ; un 429 donne le délai
+/// imposé (`retry_after` du corps JSON Discord, sinon l'en-tête `Retry-After`).
+pub(crate) async fn check_response(response: reqwest::Response) -> Result<(), NotifyError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let header_wait = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok());
+    let body = response.text().await.unwrap_or_default();
+    if status.as_u16() == 429 {
+        let body_wait = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("retry_after").and_then(|r| r.as_f64()));
+        let secs = body_wait.or(header_wait).unwrap_or(5.0).clamp(0.0, 3_600.0);
+        return Err(NotifyError::RateLimited { retry_after: Duration::from_secs_f64(secs) });
+    }
+    Err(NotifyError::Status { status: status.as_u16(), body })
+}
+
+/// Destination de notifications.
+pub trait Notifier: Send + Sync {
+    /// Envoie l'événement ; `attachments` respecte déjà `attachment_limit`.
+    fn send<'a>(&'a self, event: &'a Event, attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>>;
+
+    /// Taille totale maximale des pièces jointes d'un envoi, 0 si le sink
+    /// n'en accepte pas.
+    fn attachment_limit(&self) -> usize {
+        0
+    }
+}
+
+/// Construit un sink à partir de sa configuration.
+pub fn build_sink(name: &str, config: &SinkConfig, client: &Client, templates: &Arc<Templates>) -> Arc<dyn Notifier> {
+    let templates = SinkTemplates::new(templates.clone(), name, config.type_name());
+    match config {
+        SinkConfig::Discord { url } => Arc::new(discord::DiscordNotifier::new(client.clone(), url, templates)),
+        SinkConfig::Webhook { url } => Arc::new(webhook::WebhookNotifier::new(client.clone(), url, templates)),
+        SinkConfig::Slack { url } => Arc::new(slack::SlackNotifier::new(client.clone(), url, templates)),
+        SinkConfig::Email { host, port, from, to, subject_prefix } => Arc::new(email::EmailNotifier::new(
+            host,
+            *port,
+            from,
+            to.clone(),
+            subject_prefix,
+            templates,
+        )),
+        SinkConfig::Stdout => Arc::new(local::StdoutNotifier::new(templates)),
+        SinkConfig::File { path, attachments_dir } => {
+            Arc::new(local::FileNotifier::new(path).with_attachments_dir(attachments_dir.as_deref()))
+        }
+    }
+}
+
+/// Routeur : envoie chaque événement aux sinks configurés pour son type,
+/// plus ceux des règles de tags (`tag_routes`) qui concernent son pseudo.
+///
+/// Avec une outbox, `emit` écrit d'abord chaque notification en base et les
+/// workers (`spawn_outbox_worker`, un par sink) se chargent de l'envoi et des
+/// réessais.
+#[derive(Default)]
+pub struct Dispatcher {
+    sinks: HashMap<String, Arc<dyn Notifier>>,
+    routes: HashMap<EventKind, Vec<String>>,
+    tag_routes: Vec<TagRule>,
+    outbox: Option<Arc<Outbox>>,
+    /// Réveil du worker de chaque sink quand une notification arrive.
+    wakes: HashMap<String, Arc<Notify>>,
+    templates: Arc<Templates>,
+    attachments: AttachmentConfig,
+    zones: Vec<chrono_tz::Tz>,
+    digest: Option<DigestBuffer>,
+    quiet_hours: QuietHoursConfig,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        // un seul client HTTP (pool de connexions) partagé par tous les sinks
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        let mut dispatcher = Self {
+            templates: Arc::new(
+                Templates::load(&config.templates_dir, config.timezones().to_vec()).with_tag_routes(config.tag_routes.clone()),
+            ),
+            attachments: config.attachments.clone(),
+            zones: config.timezones().to_vec(),
+            digest: config.digest.enabled.then(|| DigestBuffer::new(config.digest.clone())),
+            quiet_hours: config.quiet_hours.clone(),
+            ..Self::new()
+        };
+        for (name, sink) in &config.sinks {
+            let sink = build_sink(name, sink, &client, &dispatcher.templates);
+            dispatcher = dispatcher.with_sink(name, sink);
+        }
+        for (kind, names) in &config.routes {
+            for name in names {
+                if !dispatcher.sinks.contains_key(name) {
+                    warn!(kind = ?kind, sink = %name, "route vers un sink inconnu");
+                }
+            }
+            dispatcher.routes.insert(*kind, names.clone());
+        }
+        for rule in &config.tag_routes {
+            for name in rule.sinks.iter().filter(|name| !dispatcher.sinks.contains_key(*name)) {
+                warn!(tags = ?rule.tags, sink = %name, "route de tags vers un sink inconnu");
+            }
+        }
+        dispatcher.tag_routes = config.tag_routes.clone();
+        dispatcher
+    }
+
+    pub fn with_sink(mut self, name: &str, sink: Arc<dyn Notifier>) -> Self {
+        self.sinks.insert(name.to_string(), sink);
+        self.wakes.insert(name.to_string(), Arc::new(Notify::new()));
+        self
+    }
+
+    pub fn with_outbox(mut self, outbox: Arc<Outbox>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    pub fn outbox(&self) -> Option<&Arc<Outbox>> {
+        self.outbox.as_ref()
+    }
+
+    /// Templates partagés par les sinks.
+    pub fn templates(&self) -> &Arc<Templates> {
+        &self.templates
+    }
+
+    pub fn sink(&self, name: &str) -> Option<&Arc<dyn Notifier>> {
+        self.sinks.get(name)
+    }
+
+    pub fn route(mut self, kind: EventKind, sinks: &[&str]) -> Self {
+        self.routes.insert(kind, sinks.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    pub fn tag_route(mut self, rule: TagRule) -> Self {
+        self.tag_routes.push(rule);
+        self
+    }
+
+    /// Sinks destinataires d'un type d'événement.
+    pub fn sinks_for(&self, kind: EventKind) -> Vec<(&str, &Arc<dyn Notifier>)> {
+        self.routes
+            .get(&kind)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.sinks.get_key_value(name))
+            .map(|(name, sink)| (name.as_str(), sink))
+            .collect()
+    }
+
+    /// Sinks destinataires d'un événement : ceux de son type, puis ceux des
+    /// règles de tags qui s'y appliquent, chacun une seule fois. Une règle
+    /// `exclusive` qui s'applique écarte les sinks du type.
+    pub fn sinks_for_event(&self, event: &Event) -> Vec<(&str, &Arc<dyn Notifier>)> {
+        let rules: Vec<&TagRule> = self.tag_routes.iter().filter(|rule| rule.matches(event)).collect();
+        let mut sinks = if rules.iter().any(|rule| rule.exclusive) { Vec::new() } else { self.sinks_for(event.kind()) };
+        for rule in rules {
+            for (name, sink) in rule.sinks.iter().filter_map(|name| self.sinks.get_key_value(name)) {
+                if !sinks.iter().any(|(known, _)| *known == name) {
+                    sinks.push((name.as_str(), sink));
+                }
+            }
+        }
+        sinks
+    }
+
+    pub fn with_attachments(mut self, attachments: AttachmentConfig) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    pub fn with_digest(mut self, digest: digest::DigestConfig) -> Self {
+        self.digest = Some(DigestBuffer::new(digest));
+        self
+    }
+
+    pub fn with_quiet_hours(mut self, quiet_hours: QuietHoursConfig) -> Self {
+        self.quiet_hours = quiet_hours;
+        self
+    }
+
+    /// Envoie l'événement à tous ses sinks, en parallèle, avec les pièces
+    /// jointes que chaque sink accepte.
+    pub async fn dispatch(&self, event: &Event, attachments: &[Attachment]) -> Vec<(String, Result<(), NotifyError>)> {
+        let sends = self.sinks_for_event(event).into_iter().map(|(name, sink)| async move {
+            let attachments = fit(name, sink.attachment_limit(), attachments);
+            (name.to_string(), sink.send(event, &attachments).await)
+        });
+        join_all(sends).await
+    }
+
+    /// Envoi en tâche de fond, avec les pièces jointes générées pour
+    /// l'événement (voir `AttachmentConfig`) avant sa mise en file, pour que
+    /// les événements d'un même appelant restent dans l'ordre. En mode
+    /// résumé, l'événement peut être retenu jusqu'au prochain résumé.
+    pub async fn emit(self: &Arc<Self>, event: Event) {
+        let event = match &self.digest {
+            Some(digest) => match digest.hold(event) {
+                Ok(()) => return,
+                Err(event) => event,
+            },
+            None => event,
+        };
+        let attachments = if self.attachments.applies_to(&event) {
+            self.attachments.build(&event, &self.zones).await
+        } else {
+            Vec::new()
+        };
+        self.emit_with(event, attachments).await;
+    }
+
+    /// Envoi en tâche de fond avec des pièces jointes explicites, vers les
+    /// sinks routés pour l'événement.
+    pub async fn emit_with(self: &Arc<Self>, event: Event, attachments: Vec<Attachment>) {
+        let sinks: Vec<String> = self.sinks_for_event(&event).into_iter().map(|(name, _)| name.to_string()).collect();
+        for name in sinks {
+            self.emit_to(&name, event.clone(), &attachments).await;
+        }
+    }
+
+    /// Envoi vers un sink donné. Avec une outbox, la notification et ses
+    /// pièces jointes sont persistées avant tout envoi ; sinon les échecs
+    /// sont seulement journalisés. Pendant les heures calmes, l'envoi d'une
+    /// notification non urgente est repoussé à leur fin.
+    async fn emit_to(&self, name: &str, event: Event, attachments: &[Attachment]) {
+        let Some(sink) = self.sinks.get(name).cloned() else {
+            return;
+        };
+        let attachments = fit(name, sink.attachment_limit(), attachments);
+        // heures calmes et fenêtres jugées à l'heure corrigée, envoi repoussé
+        // d'autant sur l'horloge locale (celle de l'outbox)
+        let now = Utc::now();
+        let corrected = CLOCK.corrected(now);
+        let send_at = now + (self.quiet_hours.send_at(&event, corrected) - corrected);
+        if send_at > now {
+            info!(kind = ?event.kind(), sink = %name, until = %send_at.to_rfc3339(), "heures calmes : notification retenue");
+        }
+        let (event, attachments) = match self.outbox.clone() {
+            None => (event, attachments),
+            Some(outbox) => {
+                // écriture attendue : les notifications d'un appelant entrent
+                // dans l'outbox dans l'ordre d'émission
+                let sink_name = name.to_string();
+                let (queued, event, attachments) = tokio::task::spawn_blocking(move || {
+                    (outbox.enqueue(&sink_name, &event, send_at, &attachments), event, attachments)
+                })
+                .await
+                .expect("appel à l'outbox interrompu");
+                match queued {
+                    Ok(_) => {
+                        if let Some(wake) = self.wakes.get(name) {
+                            wake.notify_one();
+                        }
+                        return;
+                    }
+                    // base indisponible : on tente quand même l'envoi direct
+                    Err(e) => error!(sink = %name, error = %e, "outbox indisponible, envoi direct"),
+                }
+                (event, attachments)
+            }
+        };
+        let name = name.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep((send_at - now).to_std().unwrap_or_default()).await;
+            let result = sink.send(&event, &attachments).await;
+            METRICS.notifications.inc(&name, if result.is_ok() { SendResult::Sent } else { SendResult::Failed });
+            if let Err(e) = result {
+                warn!(kind = ?event.kind(), sink = %name, error = %e, "notification non envoyée");
+            }
+        });
+    }
+
+    /// Lance la tâche qui envoie les résumés (sans effet hors mode résumé).
+    pub fn spawn_digest_flusher(self: &Arc<Self>) {
+        let Some(digest) = &self.digest else {
+            return;
+        };
+        let interval = digest.interval();
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                dispatcher.flush_digest().await;
+            }
+        });
+    }
+
+    /// Envoie les événements retenus : un résumé par sink, ou l'événement
+    /// seul (avec ses pièces jointes) si le sink n'en a reçu qu'un.
+    pub async fn flush_digest(self: &Arc<Self>) {
+        let Some(digest) = &self.digest else {
+            return;
+        };
+        let held = digest.take();
+        if held.is_empty() {
+            return;
+        }
+
+        // regroupement par sink, dans l'ordre d'arrivée
+        let mut per_sink: Vec<(String, Vec<Event>)> = Vec::new();
+        for event in held {
+            for (name, _) in self.sinks_for_event(&event) {
+                match per_sink.iter_mut().find(|(sink, _)| sink == name) {
+                    Some((_, events)) => events.push(event.clone()),
+                    None => per_sink.push((name.to_string(), vec![event.clone()])),
+                }
+            }
+        }
+
+        for (name, mut events) in per_sink {
+            let event = match events.len() {
+                1 => events.remove(0),
+                _ => Event::Digest { events },
+            };
+            let attachments = if self.attachments.applies_to(&event) {
+                self.attachments.build(&event, &self.zones).await
+            } else {
+                Vec::new()
+            };
+            self.emit_to(&name, event, &attachments).await;
+        }
+    }
+
+    /// Lance un worker par sink pour vider l'outbox (sans effet si aucune
+    /// outbox) : un sink lent ou bloqué ne retarde pas les autres, et chacun
+    /// reçoit ses notifications dans l'ordre.
+    pub fn spawn_outbox_worker(self: &Arc<Self>) {
+        let Some(outbox) = self.outbox.clone() else {
+            return;
+        };
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            // sinks configurés, plus ceux d'anciennes notifications encore en
+            // attente (sink retiré de la config : elles finissent en dead-letter)
+            let mut sinks: Vec<String> = dispatcher.sinks.keys().cloned().collect();
+            match blocking(&outbox, |outbox| outbox.pending_sinks()).await {
+                Ok(pending) => sinks.extend(pending.into_iter().filter(|sink| !dispatcher.sinks.contains_key(sink))),
+                Err(e) => error!(error = %e, "lecture de l'outbox impossible"),
+            }
+            for sink in sinks {
+                let dispatcher = dispatcher.clone();
+                let outbox = outbox.clone();
+                tokio::spawn(async move { dispatcher.run_outbox(outbox, sink).await });
+            }
+            loop {
+                let _ = blocking(&outbox, |outbox| outbox.purge_sent(chrono::Duration::days(7))).await;
+                tokio::time::sleep(Duration::from_secs(3_600)).await;
+            }
+        });
+    }
+
+    async fn run_outbox(&self, outbox: Arc<Outbox>, sink: String) {
+        // fin du blocage imposé par un 429
+        let mut blocked: Option<DateTime<Utc>> = None;
+
+        loop {
+            let now = Utc::now();
+            let name = sink.clone();
+            let due = blocking(&outbox, move |outbox| outbox.due_for(&name, now, 50)).await.unwrap_or_else(|e| {
+                error!(sink = %sink, error = %e, "lecture de l'outbox impossible");
+                Vec::new()
+            });
+            let idle = due.is_empty();
+            blocked = self.deliver_to_sink(&outbox, due, blocked).await;
+
+            // on dort jusqu'à la prochaine échéance (5 s max) ou un nouvel `emit`
+            let name = sink.clone();
+            let wait = match blocking(&outbox, move |outbox| outbox.next_due_for(&name)).await {
+                Ok(Some(next)) => (next - Utc::now()).to_std().unwrap_or_default().min(Duration::from_secs(5)),
+                _ => Duration::from_secs(5),
+            };
+            if idle || !wait.is_zero() {
+                let sleep = tokio::time::sleep(wait.max(Duration::from_millis(50)));
+                match self.wakes.get(&sink) {
+                    Some(wake) => tokio::select! {
+                        _ = sleep => {}
+                        _ = wake.notified() => {}
+                    },
+                    None => sleep.await,
+                }
+            }
+        }
+    }
+
+    /// Envoie dans l'ordre les notifications dues d'un même sink ; renvoie la
+    /// fin du blocage imposé par un 429, s'il court encore.
+    async fn deliver_to_sink(
+        &self,
+        outbox: &Arc<Outbox>,
+        entries: Vec<OutboxEntry>,
+        mut blocked: Option<DateTime<Utc>>,
+    ) -> Option<DateTime<Utc>> {
+        for entry in entries {
+            let id = entry.id;
+            if let Some(until) = blocked.filter(|until| *until > Utc::now()) {
+                let _ = blocking(outbox, move |outbox| outbox.postpone(id, until)).await;
+                continue;
+            }
+            let span = info_span!("notification", id, sink = %entry.sink, kind = ?entry.event.kind(), attempt = entry.attempts + 1);
+            let started = std::time::Instant::now();
+            let result = async {
+                match self.sinks.get(&entry.sink) {
+                    Some(sink) => match blocking(outbox, move |outbox| outbox.attachments(id)).await {
+                        Ok(attachments) => sink.send(&entry.event, &attachments).await,
+                        Err(e) => Err(NotifyError::Transport(format!("pièces jointes illisibles : {}", e))),
+                    },
+                    None => Err(NotifyError::Transport(format!("sink inconnu : {}", entry.sink))),
+                }
+            }
+            .instrument(span.clone())
+            .await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+            span.in_scope(|| match &result {
+                Ok(()) => debug!(latency_ms, "notification envoyée"),
+                Err(e) => warn!(latency_ms, error = %e, "échec d'envoi"),
+            });
+            METRICS.notifications.inc(&entry.sink, match &result {
+                Ok(()) => SendResult::Sent,
+                Err(NotifyError::RateLimited { .. }) => SendResult::RateLimited,
+                Err(_) => SendResult::Failed,
+            });
+            let stored = match result {
+                Ok(()) => blocking(outbox, move |outbox| outbox.mark_sent(id).map(|_| OutboxStatus::Sent)).await,
+                // un 429 n'est pas une tentative : la notification est seulement repoussée
+                Err(NotifyError::RateLimited { retry_after }) => {
+                    let until = Utc::now() + chrono::Duration::from_std(retry_after).unwrap_or_default();
+                    blocked = Some(until);
+                    blocking(outbox, move |outbox| outbox.mark_rate_limited(id, until).map(|_| OutboxStatus::Pending)).await
+                }
+                Err(e) => {
+                    let failed = entry.clone();
+                    blocking(outbox, move |outbox| outbox.mark_failed(&failed, &e.to_string(), None)).await
+                }
+            };
+            if let Ok(OutboxStatus::Dead) = stored {
+                METRICS.notifications.inc(&entry.sink, SendResult::Dead);
+            }
+            match stored {
+                Ok(OutboxStatus::Dead) => error!(
+                    id,
+                    kind = ?entry.event.kind(),
+                    sink = %entry.sink,
+                    attempts = entry.attempts + 1,
+                    "notification abandonnée"
+                ),
+                Ok(_) => {}
+                Err(e) => error!(id, error = %e, "mise à jour de l'outbox impossible"),
+            }
+        }
+        blocked.filter(|until| *until > Utc::now())
+    }
+}
+
+/// Appel à l'outbox (SQLite, sous verrou) hors des threads du runtime.
+async fn blocking<T, F>(outbox: &Arc<Outbox>, call: F) -> rusqlite::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Outbox) -> rusqlite::Result<T> + Send + 'static,
+{
+    let outbox = outbox.clone();
+    tokio::task::spawn_blocking(move || call(&outbox)).await.expect("appel à l'outbox interrompu")
+}
+
+#[tokio::test]
+async fn test_dispatcher_routes_by_kind() {
+    let path = std::env::temp_dir().join(format!("notify_route_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let dispatcher = Dispatcher::new()
+        .with_sink("file", Arc::new(local::FileNotifier::new(path.to_str().unwrap())))
+        .with_sink("stdout", Arc::new(local::StdoutNotifier::new(SinkTemplates::builtin("stdout"))))
+        .route(EventKind::DropWindow, &["file", "stdout"]);
+
+    let checkpoint = Event::Checkpoint { stats: CheckpointStats::default() };
+    assert!(dispatcher.dispatch(&checkpoint, &[]).await.is_empty());
+
+    let now = Utc::now();
+    let drop = Event::DropWindow { username: "Dream".into(), window: DropWindow { begin: now, end: now }, tags: vec![] };
+    let results = dispatcher.dispatch(&drop, &[]).await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|(_, r)| r.is_ok()));
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    let parsed: Event = serde_json::from_str(written.lines().next().unwrap()).unwrap();
+    assert_eq!(parsed.kind(), EventKind::DropWindow);
+    std::fs::remove_file(&path).unwrap();
+
+    // une règle de tags ajoute ses sinks aux pseudos concernés
+    let dispatcher = dispatcher.route(EventKind::DropWindow, &["stdout"]).tag_route(TagRule {
+        tags: vec!["3c".into()],
+        kinds: vec![],
+        sinks: vec!["file".into(), "stdout".into()],
+        mention: None,
+        exclusive: false,
+    });
+    let names = |event: &Event| dispatcher.sinks_for_event(event).into_iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>();
+    assert_eq!(names(&drop), vec!["stdout"]);
+    let tagged = Event::DropWindow { username: "abc".into(), window: DropWindow { begin: now, end: now }, tags: vec!["3c".into()] };
+    assert_eq!(names(&tagged), vec!["stdout", "file"]);
+
+    // une règle exclusive remplace les routes du type
+    let dispatcher = dispatcher.tag_route(TagRule {
+        tags: vec!["vip".into()],
+        kinds: vec![],
+        sinks: vec!["file".into()],
+        mention: None,
+        exclusive: true,
+    });
+    let vip = Event::DropWindow { username: "abc".into(), window: DropWindow { begin: now, end: now }, tags: vec!["vip".into()] };
+    assert_eq!(dispatcher.sinks_for_event(&vip).into_iter().map(|(name, _)| name).collect::<Vec<_>>(), vec!["file"]);
+    assert_eq!(dispatcher.sinks_for_event(&drop).len(), 1);
+}
+
+#[tokio::test]
+async fn test_outbox_worker_delivers_and_retries() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Sink qui répond 429 au premier envoi puis accepte.
+    struct Flaky(AtomicUsize);
+
+    impl Notifier for Flaky {
+        fn send<'a>(&'a self, _event: &'a Event, _attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+            Box::pin(async move {
+                match self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    0 => Err(NotifyError::RateLimited { retry_after: Duration::from_millis(100) }),
+                    _ => Ok(()),
+                }
+            })
+        }
+    }
+
+    /// Sink qui ne répond jamais à temps.
+    struct Stuck;
+
+    impl Notifier for Stuck {
+        fn send<'a>(&'a self, _event: &'a Event, _attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            })
+        }
+    }
+
+    let outbox = Arc::new(
+        Outbox::open(super::storage::open_memory_db().unwrap(), Default::default()).unwrap(),
+    );
+    let flaky = Arc::new(Flaky(AtomicUsize::new(0)));
+    let dispatcher = Arc::new(
+        Dispatcher::new()
+            .with_sink("stuck", Arc::new(Stuck))
+            .with_sink("flaky", flaky.clone())
+            .route(EventKind::Checkpoint, &["stuck", "flaky"])
+            .with_outbox(outbox.clone()),
+    );
+    dispatcher.spawn_outbox_worker();
+    dispatcher.emit(Event::Checkpoint { stats: CheckpointStats::default() }).await;
+
+    // le sink bloqué ne retient pas l'autre
+    let flaky_entry = || outbox.list(None, 10).unwrap().into_iter().find(|e| e.sink == "flaky").unwrap();
+    for _ in 0..40 {
+        if flaky_entry().status == OutboxStatus::Sent {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let entry = flaky_entry();
+    assert_eq!(entry.status, OutboxStatus::Sent);
+    assert_eq!(flaky.0.load(Ordering::SeqCst), 2);
+    // le 429 n'a pas compté comme tentative
+    assert_eq!(entry.attempts, 1);
+}
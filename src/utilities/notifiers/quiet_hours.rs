@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{Event, EventKind};
+
+/// Heures calmes : pendant la plage `start` → `end` (heure locale de
+/// `timezone`, à cheval sur minuit si `end` < `start`), les notifications non
+/// urgentes restent dans l'outbox jusqu'à `end`.
+///
+/// Sont urgentes : les escalades, les alertes du watchdog, les types de
+/// `bypass` et les événements d'une fenêtre qui s'ouvre dans moins de
+/// `imminent_minutes`. Un événement retenu part au plus tard quand sa
+/// fenêtre devient imminente.
+///
+/// Les escalades elles-mêmes sont désactivées par défaut
+/// (`escalation.enabled`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietHoursConfig {
+    pub enabled: bool,
+    pub timezone: String,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub bypass: Vec<EventKind>,
+    pub imminent_minutes: i64,
+    /// `timezone` analysé, au premier appel de `zone`.
+    #[serde(skip)]
+    zone: OnceCell<Tz>,
+}
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timezone: "Europe/Paris".to_string(),
+            start: NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            // les checkpoints accumulés pendant la nuit n'ont plus d'intérêt le matin
+            bypass: vec![EventKind::Checkpoint],
+            imminent_minutes: 60,
+            zone: OnceCell::new(),
+        }
+    }
+}
+
+impl QuietHoursConfig {
+    fn zone(&self) -> Tz {
+        *self.zone.get_or_init(|| {
+            self.timezone.parse().unwrap_or_else(|_| {
+                warn!(timezone = %self.timezone, "fuseau des heures calmes inconnu, UTC utilisé");
+                Tz::UTC
+            })
+        })
+    }
+
+    /// Fin de la plage calme en cours à `now`, `None` hors plage.
+    pub fn quiet_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.start == self.end {
+            return None;
+        }
+        let tz = self.zone();
+        let local = now.with_timezone(&tz);
+        let time = local.time();
+        let quiet = if self.start < self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        };
+        if !quiet {
+            return None;
+        }
+        let mut day = local.date_naive();
+        if time >= self.end {
+            day = day.succ_opt()?;
+        }
+        // heure de fin inexistante (changement d'heure) : une heure plus tard
+        let end = day.and_time(self.end);
+        let release = tz
+            .from_local_datetime(&end)
+            .earliest()
+            .or_else(|| tz.from_local_datetime(&(end + Duration::hours(1))).earliest())?;
+        Some(release.with_timezone(&Utc))
+    }
+
+    pub fn is_urgent(&self, event: &Event, now: DateTime<Utc>) -> bool {
+        matches!(event, Event::Escalation { .. } | Event::Watchdog { .. })
+            || self.bypass.contains(&event.kind())
+            || event.window().is_some_and(|w| w.begin - now <= Duration::minutes(self.imminent_minutes) && w.end > now)
+            || matches!(event, Event::Digest { events } if events.iter().any(|e| self.is_urgent(e, now)))
+    }
+
+    /// Instant où la fenêtre à venir de l'événement (la plus proche pour un
+    /// résumé) devient imminente.
+    fn imminent_at(&self, event: &Event, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match event {
+            Event::Digest { events } => events.iter().filter_map(|e| self.imminent_at(e, now)).min(),
+            _ => event.window().filter(|w| w.end > now).map(|w| w.begin - Duration::minutes(self.imminent_minutes)),
+        }
+    }
+
+    /// Heure d'envoi de l'événement : `now`, ou s'il n'est pas urgent la fin
+    /// des heures calmes, avancée au moment où sa fenêtre devient imminente.
+    pub fn send_at(&self, event: &Event, now: DateTime<Utc>) -> DateTime<Utc> {
+        if !self.enabled || self.is_urgent(event, now) {
+            return now;
+        }
+        let Some(until) = self.quiet_until(now) else {
+            return now;
+        };
+        match self.imminent_at(event, now) {
+            Some(imminent) => until.min(imminent).max(now),
+            None => until,
+        }
+    }
+}
+
+#[test]
+fn test_quiet_hours_hold_until_end() {
+    use crate::utilities::sql_management::DropWindow;
+
+    let config = QuietHoursConfig { enabled: true, ..Default::default() };
+    let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+    // 23:30 à Paris (été, UTC+2) → retenu jusqu'à 08:00 le lendemain
+    let night = at("2025-07-14T21:30:00Z");
+    let morning = at("2025-07-15T06:00:00Z");
+    assert_eq!(config.quiet_until(night), Some(morning));
+    assert_eq!(config.quiet_until(at("2025-07-15T03:00:00Z")), Some(morning));
+    assert_eq!(config.quiet_until(at("2025-07-15T12:00:00Z")), None);
+
+    let window = |begin: DateTime<Utc>| DropWindow { begin, end: begin + Duration::minutes(2) };
+    let later = Event::DropWindow { username: "Dream".into(), window: window(night + Duration::hours(12)), tags: vec![] };
+    let soon = Event::DropWindow { username: "Dream".into(), window: window(night + Duration::minutes(30)), tags: vec![] };
+    assert_eq!(config.send_at(&later, night), morning);
+    // fenêtre ouverte avant la fin des heures calmes : envoi quand elle devient imminente
+    let dawn = Event::DropWindow { username: "Dream".into(), window: window(night + Duration::hours(5)), tags: vec![] };
+    assert_eq!(config.send_at(&dawn, night), night + Duration::hours(4));
+    let ended = Event::WindowEnded { username: "Dream".into(), window: window(night - Duration::hours(1)), tags: vec![] };
+    assert_eq!(config.send_at(&ended, night), morning);
+    assert_eq!(config.send_at(&soon, night), night);
+    assert_eq!(config.send_at(&Event::Checkpoint { stats: Default::default() }, night), night);
+    let digest = Event::Digest { events: vec![later.clone(), soon] };
+    assert_eq!(config.send_at(&digest, night), night);
+    assert_eq!(QuietHoursConfig::default().send_at(&later, night), night);
+}
@@ -0,0 +1,39 @@
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde_json::json;
+
+use super::attachments::Attachment;
+use super::{check_response, Event, Notifier, NotifyError};
+use crate::utilities::templates::SinkTemplates;
+
+/// Incoming webhook au format Slack (`{"text": …}`, compris aussi par
+/// Mattermost, Rocket.Chat…). Les incoming webhooks n'acceptent pas de
+/// fichiers : les pièces jointes ne sont pas transmises (il faudrait l'API
+/// `files.upload` avec un token de bot).
+pub struct SlackNotifier {
+    client: Client,
+    url: String,
+    templates: SinkTemplates,
+}
+
+impl SlackNotifier {
+    pub fn new(client: Client, url: &str, templates: SinkTemplates) -> Self {
+        Self { client, url: url.to_string(), templates }
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn send<'a>(&'a self, event: &'a Event, _attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+        Box::pin(async move {
+            let message = self.templates.render(event);
+            let text = if message.title.is_empty() {
+                message.body
+            } else {
+                format!("*{}*\n{}", message.title, message.body)
+            };
+            let payload = json!({ "text": text });
+            let response = self.client.post(&self.url).json(&payload).send().await?;
+            check_response(response).await
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::Utc;
+use futures::future::BoxFuture;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde_json::json;
+
+use super::attachments::Attachment;
+use super::{check_response, Event, Notifier, NotifyError};
+use crate::utilities::templates::SinkTemplates;
+
+const ATTACHMENT_LIMIT: usize = 25 * 1024 * 1024;
+
+/// Webhook JSON générique : l'événement sérialisé tel quel, plus son rendu
+/// par les templates (`title`, `text`).
+///
+/// ```json
+/// { "kind": "drop_window", "at": "…", "title": "…", "text": "…", "data": { "event": "drop_window", … } }
+/// ```
+///
+/// Avec des pièces jointes, la requête devient `multipart/form-data` : ce JSON
+/// dans le champ `payload` puis un champ `files[i]` par fichier.
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    templates: SinkTemplates,
+}
+
+impl WebhookNotifier {
+    pub fn new(client: Client, url: &str, templates: SinkTemplates) -> Self {
+        Self { client, url: url.to_string(), templates }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn send<'a>(&'a self, event: &'a Event, attachments: &'a [Attachment]) -> BoxFuture<'a, Result<(), NotifyError>> {
+        Box::pin(async move {
+            let message = self.templates.render(event);
+            let payload = json!({
+                "kind": event.kind(),
+                "at": Utc::now(),
+                "title": message.title,
+                "text": message.body,
+                "data": event,
+            });
+            let request = if attachments.is_empty() {
+                self.client.post(&self.url).json(&payload)
+            } else {
+                let mut form = Form::new().text("payload", payload.to_string());
+                for (i, attachment) in attachments.iter().enumerate() {
+                    let part = Part::bytes(attachment.data.clone())
+                        .file_name(attachment.filename.clone())
+                        .mime_str(&attachment.content_type)?;
+                    form = form.part(format!("files[{}]", i), part);
+                }
+                self.client.post(&self.url).multipart(form)
+            };
+            let response = request.send().await?;
+            check_response(response).await
+        })
+    }
+
+    fn attachment_limit(&self) -> usize {
+        ATTACHMENT_LIMIT
+    }
+}
@@ -0,0 +1,379 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::notifiers::attachments::Attachment;
+use super::notifiers::Event;
+use super::storage::Db;
+
+/// État d'une notification dans l'outbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    Pending,
+    Sent,
+    /// Abandonnée après `max_attempts` échecs ; relançable via la CLI.
+    Dead,
+}
+
+impl OutboxStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            OutboxStatus::Pending => "pending",
+            OutboxStatus::Sent => "sent",
+            OutboxStatus::Dead => "dead",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "pending" => Some(OutboxStatus::Pending),
+            "sent" => Some(OutboxStatus::Sent),
+            "dead" => Some(OutboxStatus::Dead),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OutboxStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Politique de réessai : backoff exponentiel plafonné puis dead-letter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 12, base_delay_secs: 5, max_delay_secs: 3_600 }
+    }
+}
+
+impl RetryPolicy {
+    /// Délai avant la tentative suivante, après `attempts` échecs (avec ±20 % de gigue).
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let exp = self.base_delay_secs.saturating_mul(1u64 << attempts.saturating_sub(1).min(20));
+        let secs = exp.min(self.max_delay_secs) as f64 * rand::rng().random_range(0.8..1.2);
+        Duration::milliseconds((secs * 1000.0) as i64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub sink: String,
+    pub event: Event,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+const COLUMNS: &str = "id, sink, event, status, attempts, next_attempt_at, last_error, created_at, sent_at";
+
+fn entry_from_row(row: &Row<'_>) -> rusqlite::Result<OutboxEntry> {
+    let event: String = row.get(2)?;
+    let status: String = row.get(3)?;
+    Ok(OutboxEntry {
+        id: row.get(0)?,
+        sink: row.get(1)?,
+        event: serde_json::from_str(&event)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e)))?,
+        status: OutboxStatus::parse(&status).unwrap_or(OutboxStatus::Dead),
+        attempts: row.get(4)?,
+        next_attempt_at: row.get(5)?,
+        last_error: row.get(6)?,
+        created_at: row.get(7)?,
+        sent_at: row.get(8)?,
+    })
+}
+
+/// File persistante des notifications : chaque notification y est écrite
+/// avant toute tentative d'envoi, une par sink destinataire.
+pub struct Outbox {
+    db: Db,
+    policy: RetryPolicy,
+}
+
+impl Outbox {
+    pub fn open(db: Db, policy: RetryPolicy) -> rusqlite::Result<Self> {
+        db.lock().execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sink TEXT NOT NULL,
+                event TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at TEXT NOT NULL,
+                last_error TEXT,
+                created_at TEXT NOT NULL,
+                sent_at TEXT
+            );
+            CREATE INDEX IF NOT EXISTS outbox_due ON outbox (status, next_attempt_at);
+            CREATE TABLE IF NOT EXISTS outbox_attachments (
+                outbox_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                filename TEXT NOT NULL,
+                content_type TEXT NOT NULL,
+                data BLOB NOT NULL,
+                PRIMARY KEY (outbox_id, position)
+            );
+            ",
+        )?;
+        Ok(Self { db, policy })
+    }
+
+    /// Ajoute une notification et ses pièces jointes (dans une transaction).
+    pub fn enqueue(
+        &self,
+        sink: &str,
+        event: &Event,
+        not_before: DateTime<Utc>,
+        attachments: &[Attachment],
+    ) -> rusqlite::Result<i64> {
+        let event = serde_json::to_string(event).expect("Event is always serializable");
+        let mut conn = self.db.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO outbox (sink, event, next_attempt_at, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![sink, event, not_before, Utc::now()],
+        )?;
+        let id = tx.last_insert_rowid();
+        for (position, attachment) in attachments.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO outbox_attachments (outbox_id, position, filename, content_type, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, position as i64, attachment.filename, attachment.content_type, attachment.data],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Pièces jointes d'une notification, dans leur ordre d'ajout.
+    pub fn attachments(&self, id: i64) -> rusqlite::Result<Vec<Attachment>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT filename, content_type, data FROM outbox_attachments WHERE outbox_id = ?1 ORDER BY position",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok(Attachment { filename: row.get(0)?, content_type: row.get(1)?, data: row.get(2)? })
+        })?;
+        rows.collect()
+    }
+
+    /// Nom et taille des pièces jointes, sans charger leur contenu.
+    pub fn attachment_sizes(&self, id: i64) -> rusqlite::Result<Vec<(String, usize)>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT filename, length(data) FROM outbox_attachments WHERE outbox_id = ?1 ORDER BY position",
+        )?;
+        let rows = stmt.query_map(params![id], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+        rows.collect()
+    }
+
+    /// Notifications en attente dont l'heure de tentative est passée.
+    pub fn due(&self, now: DateTime<Utc>, limit: usize) -> rusqlite::Result<Vec<OutboxEntry>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {COLUMNS} FROM outbox WHERE status = 'pending' AND next_attempt_at <= ?1
+             ORDER BY next_attempt_at LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![now, limit as i64], entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Notifications en attente d'un sink dont l'heure de tentative est passée.
+    pub fn due_for(&self, sink: &str, now: DateTime<Utc>, limit: usize) -> rusqlite::Result<Vec<OutboxEntry>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {COLUMNS} FROM outbox WHERE status = 'pending' AND sink = ?1 AND next_attempt_at <= ?2
+             ORDER BY next_attempt_at, id LIMIT ?3"
+        ))?;
+        let rows = stmt.query_map(params![sink, now, limit as i64], entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Prochaine échéance parmi les notifications en attente d'un sink.
+    pub fn next_due_for(&self, sink: &str) -> rusqlite::Result<Option<DateTime<Utc>>> {
+        self.db
+            .lock()
+            .query_row("SELECT MIN(next_attempt_at) FROM outbox WHERE status = 'pending' AND sink = ?1", params![sink], |row| row.get(0))
+            .optional()
+            .map(Option::flatten)
+    }
+
+    /// Sinks ayant des notifications en attente.
+    pub fn pending_sinks(&self) -> rusqlite::Result<Vec<String>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare("SELECT DISTINCT sink FROM outbox WHERE status = 'pending'")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Prochaine échéance parmi les notifications en attente.
+    pub fn next_due(&self) -> rusqlite::Result<Option<DateTime<Utc>>> {
+        self.db
+            .lock()
+            .query_row("SELECT MIN(next_attempt_at) FROM outbox WHERE status = 'pending'", [], |row| row.get(0))
+            .optional()
+            .map(Option::flatten)
+    }
+
+    pub fn mark_sent(&self, id: i64) -> rusqlite::Result<()> {
+        self.db.lock().execute(
+            "UPDATE outbox SET status = 'sent', attempts = attempts + 1, last_error = NULL, sent_at = ?2 WHERE id = ?1",
+            params![id, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    /// Enregistre un échec : replanifie (délai imposé par le sink ou backoff)
+    /// ou passe en dead-letter. Renvoie le nouvel état.
+    pub fn mark_failed(
+        &self,
+        entry: &OutboxEntry,
+        error: &str,
+        retry_after: Option<std::time::Duration>,
+    ) -> rusqlite::Result<OutboxStatus> {
+        let attempts = entry.attempts + 1;
+        let status = if attempts >= self.policy.max_attempts { OutboxStatus::Dead } else { OutboxStatus::Pending };
+        let delay = match retry_after {
+            Some(wait) => Duration::from_std(wait).unwrap_or(Duration::seconds(60)),
+            None => self.policy.delay(attempts),
+        };
+        self.db.lock().execute(
+            "UPDATE outbox SET status = ?2, attempts = ?3, next_attempt_at = ?4, last_error = ?5 WHERE id = ?1",
+            params![entry.id, status.as_str(), attempts, Utc::now() + delay, error],
+        )?;
+        Ok(status)
+    }
+
+    /// Délai imposé par le sink (429) : repoussée sans compter de tentative.
+    pub fn mark_rate_limited(&self, id: i64, until: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.db.lock().execute(
+            "UPDATE outbox SET next_attempt_at = ?2, last_error = '429' WHERE id = ?1",
+            params![id, until],
+        )?;
+        Ok(())
+    }
+
+    /// Repousse une notification sans compter de tentative (sink temporairement bloqué).
+    pub fn postpone(&self, id: i64, until: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.db
+            .lock()
+            .execute("UPDATE outbox SET next_attempt_at = ?2 WHERE id = ?1", params![id, until])?;
+        Ok(())
+    }
+
+    /// Remet une notification (envoyée ou morte) en file pour un envoi immédiat.
+    pub fn resend(&self, id: i64) -> rusqlite::Result<bool> {
+        let changed = self.db.lock().execute(
+            "UPDATE outbox SET status = 'pending', attempts = 0, next_attempt_at = ?2, sent_at = NULL WHERE id = ?1",
+            params![id, Utc::now()],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Remet toutes les notifications mortes en file.
+    pub fn resend_dead(&self) -> rusqlite::Result<usize> {
+        self.db.lock().execute(
+            "UPDATE outbox SET status = 'pending', attempts = 0, next_attempt_at = ?1 WHERE status = 'dead'",
+            params![Utc::now()],
+        )
+    }
+
+    pub fn get(&self, id: i64) -> rusqlite::Result<Option<OutboxEntry>> {
+        self.db
+            .lock()
+            .query_row(&format!("SELECT {COLUMNS} FROM outbox WHERE id = ?1"), params![id], entry_from_row)
+            .optional()
+    }
+
+    /// Dernières notifications, éventuellement filtrées par état.
+    pub fn list(&self, status: Option<OutboxStatus>, limit: usize) -> rusqlite::Result<Vec<OutboxEntry>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {COLUMNS} FROM outbox WHERE ?1 IS NULL OR status = ?1 ORDER BY id DESC LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![status.map(OutboxStatus::as_str), limit as i64], entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Nombre de notifications par état.
+    pub fn counts(&self) -> rusqlite::Result<Vec<(OutboxStatus, usize)>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM outbox GROUP BY status")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        let mut counts = Vec::new();
+        for row in rows {
+            let (status, count) = row?;
+            if let Some(status) = OutboxStatus::parse(&status) {
+                counts.push((status, count as usize));
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Supprime les notifications envoyées depuis plus de `keep`, avec leurs
+    /// pièces jointes.
+    pub fn purge_sent(&self, keep: Duration) -> rusqlite::Result<usize> {
+        let mut conn = self.db.lock();
+        let tx = conn.transaction()?;
+        let before = Utc::now() - keep;
+        tx.execute(
+            "DELETE FROM outbox_attachments WHERE outbox_id IN
+                (SELECT id FROM outbox WHERE status = 'sent' AND sent_at < ?1)",
+            params![before],
+        )?;
+        let purged = tx.execute("DELETE FROM outbox WHERE status = 'sent' AND sent_at < ?1", params![before])?;
+        tx.commit()?;
+        Ok(purged)
+    }
+}
+
+#[test]
+fn test_outbox_retry_and_dead_letter() -> rusqlite::Result<()> {
+    let policy = RetryPolicy { max_attempts: 2, base_delay_secs: 1, max_delay_secs: 1 };
+    let outbox = Outbox::open(super::storage::open_memory_db()?, policy)?;
+    let event = Event::Checkpoint { stats: Default::default() };
+
+    let card = Attachment::new("dream.gif", "image/gif", vec![1, 2, 3]);
+    let id = outbox.enqueue("discord_logs", &event, Utc::now(), std::slice::from_ref(&card))?;
+    assert_eq!(outbox.attachments(id)?, vec![card]);
+    let due = outbox.due(Utc::now(), 10)?;
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].sink, "discord_logs");
+
+    // 1er échec avec retry_after imposé : replanifié, plus dû tout de suite
+    let status = outbox.mark_failed(&due[0], "429", Some(std::time::Duration::from_secs(30)))?;
+    assert_eq!(status, OutboxStatus::Pending);
+    assert!(outbox.due(Utc::now(), 10)?.is_empty());
+    assert!(outbox.due(Utc::now() + Duration::seconds(31), 10)?.len() == 1);
+
+    // 2e échec : dead-letter
+    let entry = outbox.get(id)?.unwrap();
+    assert_eq!(outbox.mark_failed(&entry, "500", None)?, OutboxStatus::Dead);
+    assert_eq!(outbox.list(Some(OutboxStatus::Dead), 10)?.len(), 1);
+
+    assert_eq!(outbox.resend_dead()?, 1);
+    let entry = outbox.due(Utc::now(), 10)?.remove(0);
+    assert_eq!(entry.attempts, 0);
+    outbox.mark_sent(entry.id)?;
+    assert_eq!(outbox.counts()?, vec![(OutboxStatus::Sent, 1)]);
+    assert_eq!(outbox.purge_sent(Duration::seconds(-1))?, 1);
+    assert!(outbox.attachments(id)?.is_empty());
+    Ok(())
+}
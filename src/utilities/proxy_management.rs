@@ -1,3 +1,91 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use super::stats::Outcome;
+
+/// Santé des proxies, par index de client : requêtes abouties ou non et
+/// résultat de la dernière requête.
+pub struct ProxyHealth {
+    proxies: Vec<ProxyStats>,
+}
+
+struct ProxyStats {
+    label: String,
+    ok: AtomicU64,
+    failed: AtomicU64,
+    /// 0 : jamais utilisé, sinon 1 + index dans `Outcome::ALL`.
+    last: AtomicU8,
+}
+
+/// Répartition des proxies selon leur dernière requête.
+#[derive(Debug, Clone, Default)]
+pub struct ProxySummary {
+    pub total: usize,
+    pub unused: usize,
+    pub healthy: usize,
+    pub rate_limited: usize,
+    pub failing: usize,
+    /// Proxies au plus faible taux de réussite : (adresse, réussies, échouées).
+    pub worst: Vec<(String, u64, u64)>,
+}
+
+impl ProxyHealth {
+    pub fn new(labels: Vec<String>) -> Self {
+        let proxies = labels
+            .into_iter()
+            .map(|label| ProxyStats { label, ok: AtomicU64::new(0), failed: AtomicU64::new(0), last: AtomicU8::new(0) })
+            .collect();
+        Self { proxies }
+    }
+
+    pub fn record(&self, client_id: usize, outcome: Outcome) {
+        let Some(proxy) = self.proxies.get(client_id) else {
+            return;
+        };
+        match outcome {
+            Outcome::Ok => proxy.ok.fetch_add(1, Ordering::Relaxed),
+            _ => proxy.failed.fetch_add(1, Ordering::Relaxed),
+        };
+        let index = Outcome::ALL.iter().position(|o| *o == outcome).unwrap_or(0);
+        proxy.last.store(index as u8 + 1, Ordering::Relaxed);
+    }
+
+    /// Résumé, avec les `worst` proxies les moins fiables.
+    pub fn summary(&self, worst: usize) -> ProxySummary {
+        let mut summary = ProxySummary { total: self.proxies.len(), ..Default::default() };
+        let mut ranked = Vec::new();
+        for proxy in &self.proxies {
+            match proxy.last.load(Ordering::Relaxed) {
+                0 => summary.unused += 1,
+                last => match Outcome::ALL[last as usize - 1] {
+                    Outcome::Ok => summary.healthy += 1,
+                    Outcome::RateLimited => summary.rate_limited += 1,
+                    _ => summary.failing += 1,
+                },
+            }
+            let (ok, failed) = (proxy.ok.load(Ordering::Relaxed), proxy.failed.load(Ordering::Relaxed));
+            if failed > 0 {
+                ranked.push((proxy.label.clone(), ok, failed));
+            }
+        }
+        ranked.sort_by(|a, b| (a.1 * (b.1 + b.2)).cmp(&(b.1 * (a.1 + a.2))).then(b.2.cmp(&a.2)));
+        ranked.truncate(worst);
+        summary.worst = ranked;
+        summary
+    }
+}
+
+/// `hôte:port` d'une URL de proxy, sans les identifiants.
+pub fn proxy_label(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port_or_known_default()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            _ => url.to_string(),
+        },
+        Err(_) => url.rsplit('@').next().unwrap_or(url).to_string(),
+    }
+}
+
 // use std::collections::{HashMap, VecDeque};
 // use std::sync::Arc;
 // use std::time::{Duration, Instant};
@@ -0,0 +1,132 @@
+use ahash::RandomState;
+use chrono::{DateTime, Utc};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use super::username::Username;
+
+pub const QUARANTINE_PATH: &str = "quarantine.txt";
+
+/// Pseudo écarté de la rotation parce que l'API le refuse (400).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub username: String,
+    pub reason: String,
+    pub since: DateTime<Utc>,
+}
+
+/// Pseudos invalides, persistés en JSON lines pour survivre aux redémarrages.
+pub struct Quarantine {
+    entries: DashMap<Username, QuarantineEntry, RandomState>,
+    recent: Mutex<Vec<String>>,
+    path: PathBuf,
+}
+
+impl Quarantine {
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = DashMap::with_hasher(RandomState::new());
+
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<QuarantineEntry>(&line) {
+                    Ok(entry) => {
+                        entries.insert(Username::new(&entry.username), entry);
+                    }
+                    Err(e) => warn!(error = %e, line = %line, "ligne de quarantaine illisible"),
+                }
+            }
+        }
+
+        Ok(Self { entries, recent: Mutex::new(Vec::new()), path })
+    }
+
+    pub fn contains(&self, username: &Username) -> bool {
+        self.entries.contains_key(username.key())
+    }
+
+    /// Met un pseudo en quarantaine. Renvoie `false` s'il y était déjà.
+    pub fn insert(&self, username: &Username, reason: &str) -> std::io::Result<bool> {
+        // l'entrée garde le shard verrouillé : deux workers qui voient le même
+        // 400 n'écrivent qu'une seule ligne
+        let Entry::Vacant(vacant) = self.entries.entry(username.clone()) else {
+            return Ok(false);
+        };
+        let entry = QuarantineEntry {
+            username: username.display().to_string(),
+            reason: reason.to_string(),
+            since: Utc::now(),
+        };
+
+        let mut file = OpenOptions::new().append(true).create(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+        vacant.insert(entry);
+        self.recent.lock().push(username.display().to_string());
+        Ok(true)
+    }
+
+    /// Retire un pseudo de la quarantaine et réécrit le fichier.
+    pub fn release(&self, username: &str) -> std::io::Result<bool> {
+        if self.entries.remove(Username::key_of(username).as_str()).is_none() {
+            return Ok(false);
+        }
+        let mut content = String::new();
+        for entry in self.list() {
+            content.push_str(&serde_json::to_string(&entry)?);
+            content.push('\n');
+        }
+        fs::write(&self.path, content)?;
+        Ok(true)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Toutes les entrées, triées par date de mise en quarantaine.
+    pub fn list(&self) -> Vec<QuarantineEntry> {
+        let mut list: Vec<_> = self.entries.iter().map(|e| e.value().clone()).collect();
+        list.sort_by_key(|e| e.since);
+        list
+    }
+
+    /// Pseudos mis en quarantaine depuis le dernier appel (pour le checkpoint).
+    pub fn drain_recent(&self) -> Vec<String> {
+        std::mem::take(&mut *self.recent.lock())
+    }
+}
+
+#[test]
+fn test_quarantine_roundtrip() -> std::io::Result<()> {
+    let path = std::env::temp_dir().join(format!("quarantine_test_{}.txt", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let quarantine = Quarantine::load(&path)?;
+    assert!(quarantine.insert(&"Bad-Name".into(), "400 Bad Request")?);
+    assert!(!quarantine.insert(&"bad-name".into(), "400 Bad Request")?);
+    assert!(quarantine.contains(&"BAD-NAME".into()));
+    assert_eq!(quarantine.drain_recent(), vec!["Bad-Name".to_string()]);
+
+    let reloaded = Quarantine::load(&path)?;
+    assert_eq!(reloaded.len(), 1);
+    assert!(reloaded.release("bad-name")?);
+    assert!(Quarantine::load(&path)?.is_empty());
+
+    fs::remove_file(&path)
+}
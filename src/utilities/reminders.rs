@@ -0,0 +1,117 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+use super::clock::CLOCK;
+use super::notifiers::{Dispatcher, Event};
+use super::window_store::WindowStore;
+
+/// Décalage réservé au rappel de fin de fenêtre dans `reminders_sent`.
+const END_OFFSET: i64 = -1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderConfig {
+    /// Rappels avant l'ouverture, en minutes (ex. 1440 = T-24h).
+    pub before_minutes: Vec<i64>,
+    /// Prévenir aussi quand la fenêtre se termine.
+    pub at_end: bool,
+    pub check_interval_secs: u64,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self { before_minutes: vec![1_440, 60, 5], at_end: true, check_interval_secs: 10 }
+    }
+}
+
+/// Un tour du planificateur : envoie les rappels échus des fenêtres ouvertes
+/// et clôt les fenêtres terminées. L'état vit en base, donc un redémarrage
+/// reprend là où on s'était arrêté ; une fenêtre prise n'est plus `open` et
+/// ses rappels restants sont abandonnés.
+pub fn due_reminders(store: &WindowStore, config: &ReminderConfig, now: DateTime<Utc>) -> rusqlite::Result<Vec<Event>> {
+    let mut events = Vec::new();
+
+    for stored in store.open_windows()? {
+        let window = stored.window;
+
+        if now >= window.end {
+            if config.at_end && !store.reminder_sent(&stored.username, &window, END_OFFSET)? {
+                events.push(Event::WindowEnded { username: stored.username.clone(), window, tags: stored.tags.clone() });
+                store.mark_reminder_sent(&stored.username, &window, END_OFFSET)?;
+            }
+            store.mark_closed(&stored.username, &window)?;
+            continue;
+        }
+        if now >= window.begin {
+            continue;
+        }
+
+        // rappels échus et pas encore envoyés ; après une coupure on n'envoie
+        // que le plus proche de l'ouverture
+        let mut due = Vec::new();
+        for &minutes in &config.before_minutes {
+            if now >= window.begin - Duration::minutes(minutes) && !store.reminder_sent(&stored.username, &window, minutes)? {
+                due.push(minutes);
+            }
+        }
+        if let Some(&closest) = due.iter().min() {
+            events.push(Event::Reminder {
+                username: stored.username.clone(),
+                window,
+                minutes_before: closest,
+                tags: stored.tags.clone(),
+            });
+            for minutes in due {
+                store.mark_reminder_sent(&stored.username, &window, minutes)?;
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Lance le planificateur de rappels en tâche de fond.
+pub fn spawn_reminder_scheduler(store: Arc<WindowStore>, dispatcher: Arc<Dispatcher>, config: ReminderConfig) {
+    tokio::spawn(async move {
+        loop {
+            match due_reminders(&store, &config, CLOCK.corrected(Utc::now())) {
+                Ok(events) => {
+                    for event in events {
+                        dispatcher.emit(event).await;
+                    }
+                }
+                Err(e) => error!(error = %e, "planificateur de rappels"),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(config.check_interval_secs.max(1))).await;
+        }
+    });
+}
+
+#[test]
+fn test_reminders_fire_once_and_stop_on_claim() -> rusqlite::Result<()> {
+    use super::sql_management::DropWindow;
+
+    let store = WindowStore::open(super::storage::open_memory_db()?)?;
+    let config = ReminderConfig::default();
+    let begin = Utc::now() + Duration::hours(2);
+    let window = DropWindow { begin, end: begin + Duration::minutes(3) };
+    store.insert("Dream", &window, &[])?;
+    store.insert("Notch", &window, &[])?;
+
+    // T-2h : T-24h échu pour les deux, une seule fois
+    assert_eq!(due_reminders(&store, &config, Utc::now())?.len(), 2);
+    assert!(due_reminders(&store, &config, Utc::now())?.is_empty());
+
+    // Notch est pris : plus aucun rappel pour lui
+    store.mark_claimed("notch", &window)?;
+    let events = due_reminders(&store, &config, begin - Duration::minutes(4))?;
+    assert!(matches!(events.as_slice(), [Event::Reminder { username, minutes_before: 5, .. }] if username == "Dream"));
+
+    // fin de fenêtre : rappel de fin puis clôture
+    let events = due_reminders(&store, &config, window.end)?;
+    assert!(matches!(events.as_slice(), [Event::WindowEnded { .. }]));
+    assert!(store.open_windows()?.is_empty());
+    Ok(())
+}
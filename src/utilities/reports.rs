@@ -0,0 +1,482 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use super::metrics::METRICS;
+use super::notifiers::{Dispatcher, Event};
+use super::sql_management::UsernameMap;
+use super::stats::{Counts, STATS};
+use super::storage::Db;
+use super::window_store::WindowStore;
+
+/// Bilans quotidiens : les compteurs du scanner sont cumulés par jour (heure
+/// locale du premier fuseau d'affichage) dans la base, et un résumé de la
+/// veille, puis de la semaine écoulée, part chaque jour à `hour`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportsConfig {
+    pub daily: bool,
+    pub weekly: bool,
+    /// Heure locale d'envoi des résumés.
+    pub hour: u32,
+    /// Jour d'envoi du résumé hebdomadaire, qui couvre les 7 jours précédents.
+    pub weekday: Weekday,
+    /// Intervalle d'enregistrement des compteurs en base.
+    pub flush_interval_secs: u64,
+}
+
+impl Default for ReportsConfig {
+    fn default() -> Self {
+        Self { daily: true, weekly: true, hour: 8, weekday: Weekday::Mon, flush_interval_secs: 60 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Daily,
+    Weekly,
+}
+
+impl Period {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "daily" => Some(Period::Daily),
+            "weekly" => Some(Period::Weekly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Daily => "quotidien",
+            Period::Weekly => "hebdomadaire",
+        }
+    }
+
+    pub fn days(self) -> i64 {
+        match self {
+            Period::Daily => 1,
+            Period::Weekly => 7,
+        }
+    }
+}
+
+/// Bilan d'une journée, ou d'une période pour un total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rollup {
+    #[serde(flatten)]
+    pub counts: Counts,
+    /// Nouvelles fenêtres de drop détectées.
+    pub drops_detected: u64,
+    /// Pseudos repris pendant leur fenêtre.
+    pub claims_observed: u64,
+    /// Fenêtres qui se sont ouvertes, puis terminées, ce jour-là.
+    pub windows_opened: u64,
+    pub windows_closed: u64,
+    /// Pseudos suivis, et ceux vérifiés au moins une fois dans la journée ;
+    /// sur une période, sommes des journées.
+    pub names_total: u64,
+    pub names_covered: u64,
+}
+
+impl Rollup {
+    pub fn success_pct(&self) -> f64 {
+        pct(self.counts.ok, self.counts.requests())
+    }
+
+    pub fn coverage_pct(&self) -> f64 {
+        pct(self.names_covered, self.names_total)
+    }
+
+    fn add(&mut self, other: &Rollup) {
+        self.counts.add(&other.counts);
+        self.drops_detected += other.drops_detected;
+        self.claims_observed += other.claims_observed;
+        self.windows_opened += other.windows_opened;
+        self.windows_closed += other.windows_closed;
+        self.names_total += other.names_total;
+        self.names_covered += other.names_covered;
+    }
+}
+
+fn pct(n: u64, of: u64) -> f64 {
+    if of > 0 {
+        n as f64 / of as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DailyRollup {
+    pub day: NaiveDate,
+    #[serde(flatten)]
+    pub rollup: Rollup,
+}
+
+/// En-tête CSV correspondant à [`DailyRollup::csv_row`].
+pub fn rollup_csv_header() -> &'static str {
+    "day,requests,ok,rate_limited,forbidden,bad_request,errors,timeouts,success_pct,batches_ok,batches_failed,\
+     names_checked,drops_detected,claims_observed,windows_opened,windows_closed,names_total,names_covered,coverage_pct"
+}
+
+impl DailyRollup {
+    pub fn csv_row(&self) -> String {
+        let r = &self.rollup;
+        let c = &r.counts;
+        format!(
+            "{},{},{},{},{},{},{},{},{:.1},{},{},{},{},{},{},{},{},{},{:.1}",
+            self.day,
+            c.requests(),
+            c.ok,
+            c.rate_limited,
+            c.forbidden,
+            c.bad_request,
+            c.errors,
+            c.timeouts,
+            r.success_pct(),
+            c.batches_ok,
+            c.batches_failed,
+            c.names_checked,
+            r.drops_detected,
+            r.claims_observed,
+            r.windows_opened,
+            r.windows_closed,
+            r.names_total,
+            r.names_covered,
+            r.coverage_pct(),
+        )
+    }
+}
+
+/// Résumé d'une période, du `from` au `to` inclus ; `days` ne contient que
+/// les journées avec des données.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub period: Period,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub totals: Rollup,
+    pub days: Vec<DailyRollup>,
+}
+
+const COLUMNS: &str = "day, ok, rate_limited, forbidden, bad_request, errors, timeouts, batches_ok, batches_failed, \
+     names_checked, drops_detected, claims_observed, windows_opened, windows_closed, names_total, names_covered";
+
+fn rollup_from_row(row: &Row<'_>) -> rusqlite::Result<DailyRollup> {
+    Ok(DailyRollup {
+        day: row.get(0)?,
+        rollup: Rollup {
+            counts: Counts {
+                ok: row.get(1)?,
+                rate_limited: row.get(2)?,
+                forbidden: row.get(3)?,
+                bad_request: row.get(4)?,
+                errors: row.get(5)?,
+                timeouts: row.get(6)?,
+                batches_ok: row.get(7)?,
+                batches_failed: row.get(8)?,
+                names_checked: row.get(9)?,
+            },
+            drops_detected: row.get(10)?,
+            claims_observed: row.get(11)?,
+            windows_opened: row.get(12)?,
+            windows_closed: row.get(13)?,
+            names_total: row.get(14)?,
+            names_covered: row.get(15)?,
+        },
+    })
+}
+
+/// Bilans quotidiens persistés en SQLite, avec le suivi des résumés envoyés.
+pub struct RollupStore {
+    db: Db,
+}
+
+impl RollupStore {
+    pub fn open(db: Db) -> rusqlite::Result<Self> {
+        db.lock().execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS daily_rollups (
+                day TEXT PRIMARY KEY,
+                ok INTEGER NOT NULL DEFAULT 0,
+                rate_limited INTEGER NOT NULL DEFAULT 0,
+                forbidden INTEGER NOT NULL DEFAULT 0,
+                bad_request INTEGER NOT NULL DEFAULT 0,
+                errors INTEGER NOT NULL DEFAULT 0,
+                timeouts INTEGER NOT NULL DEFAULT 0,
+                batches_ok INTEGER NOT NULL DEFAULT 0,
+                batches_failed INTEGER NOT NULL DEFAULT 0,
+                names_checked INTEGER NOT NULL DEFAULT 0,
+                drops_detected INTEGER NOT NULL DEFAULT 0,
+                claims_observed INTEGER NOT NULL DEFAULT 0,
+                windows_opened INTEGER NOT NULL DEFAULT 0,
+                windows_closed INTEGER NOT NULL DEFAULT 0,
+                names_total INTEGER NOT NULL DEFAULT 0,
+                names_covered INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS reports_sent (
+                period TEXT NOT NULL,
+                period_start TEXT NOT NULL,
+                sent_at TEXT NOT NULL,
+                PRIMARY KEY (period, period_start)
+            );
+            ",
+        )?;
+        Ok(Self { db })
+    }
+
+    /// Ajoute les compteurs de `delta` à la journée `day` ; fenêtres et
+    /// couverture sont des relevés, la dernière valeur remplace la précédente.
+    pub fn accumulate(&self, day: NaiveDate, delta: &Rollup) -> rusqlite::Result<()> {
+        let c = &delta.counts;
+        self.db.lock().execute(
+            &format!(
+                "INSERT INTO daily_rollups ({COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+                 ON CONFLICT (day) DO UPDATE SET
+                    ok = ok + excluded.ok,
+                    rate_limited = rate_limited + excluded.rate_limited,
+                    forbidden = forbidden + excluded.forbidden,
+                    bad_request = bad_request + excluded.bad_request,
+                    errors = errors + excluded.errors,
+                    timeouts = timeouts + excluded.timeouts,
+                    batches_ok = batches_ok + excluded.batches_ok,
+                    batches_failed = batches_failed + excluded.batches_failed,
+                    names_checked = names_checked + excluded.names_checked,
+                    drops_detected = drops_detected + excluded.drops_detected,
+                    claims_observed = claims_observed + excluded.claims_observed,
+                    windows_opened = excluded.windows_opened,
+                    windows_closed = excluded.windows_closed,
+                    names_total = excluded.names_total,
+                    names_covered = excluded.names_covered"
+            ),
+            params![
+                day,
+                c.ok,
+                c.rate_limited,
+                c.forbidden,
+                c.bad_request,
+                c.errors,
+                c.timeouts,
+                c.batches_ok,
+                c.batches_failed,
+                c.names_checked,
+                delta.drops_detected,
+                delta.claims_observed,
+                delta.windows_opened,
+                delta.windows_closed,
+                delta.names_total,
+                delta.names_covered,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Journées du `from` au `to` inclus, dans l'ordre.
+    pub fn days(&self, from: NaiveDate, to: NaiveDate) -> rusqlite::Result<Vec<DailyRollup>> {
+        let conn = self.db.lock();
+        let mut stmt =
+            conn.prepare_cached(&format!("SELECT {COLUMNS} FROM daily_rollups WHERE day >= ?1 AND day <= ?2 ORDER BY day"))?;
+        let rows = stmt.query_map(params![from, to], rollup_from_row)?;
+        rows.collect()
+    }
+
+    /// Résumé de la période qui se termine le `to` inclus.
+    pub fn summary(&self, period: Period, to: NaiveDate) -> rusqlite::Result<Summary> {
+        let from = to - Duration::days(period.days() - 1);
+        let days = self.days(from, to)?;
+        let mut totals = Rollup::default();
+        days.iter().for_each(|day| totals.add(&day.rollup));
+        Ok(Summary { period, from, to, totals, days })
+    }
+
+    /// Marque le résumé comme envoyé ; `false` s'il l'était déjà.
+    pub fn mark_report_sent(&self, period: Period, from: NaiveDate) -> rusqlite::Result<bool> {
+        let inserted = self.db.lock().execute(
+            "INSERT OR IGNORE INTO reports_sent (period, period_start, sent_at) VALUES (?1, ?2, ?3)",
+            params![period.as_str(), from, Utc::now()],
+        )?;
+        Ok(inserted > 0)
+    }
+}
+
+/// Début d'une journée locale, en UTC (une heure plus tard si minuit
+/// n'existe pas ce jour-là).
+pub fn day_start(day: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(midnight + Duration::hours(1))).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// Dernier jour couvert par le résumé le plus récent programmé avant `now`.
+fn last_scheduled(period: Period, config: &ReportsConfig, tz: Tz, now: DateTime<Utc>) -> Option<NaiveDate> {
+    let local = now.with_timezone(&tz);
+    let today = local.date_naive();
+    let sent_on = (0..=7)
+        .map(|back| today - Duration::days(back))
+        .find(|day| (period == Period::Daily || day.weekday() == config.weekday) && (*day < today || local.hour() >= config.hour))?;
+    sent_on.pred_opt()
+}
+
+/// Résumés échus à `now` et pas encore envoyés. Après une coupure, seul le
+/// plus récent de chaque type part ; une période sans données est passée.
+pub fn due_reports(store: &RollupStore, config: &ReportsConfig, tz: Tz, now: DateTime<Utc>) -> rusqlite::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    for (period, enabled) in [(Period::Daily, config.daily), (Period::Weekly, config.weekly)] {
+        let Some(to) = last_scheduled(period, config, tz, now).filter(|_| enabled) else {
+            continue;
+        };
+        let summary = store.summary(period, to)?;
+        if store.mark_report_sent(period, summary.from)? && !summary.days.is_empty() {
+            events.push(Event::Report { summary: Box::new(summary) });
+        }
+    }
+    Ok(events)
+}
+
+/// Totaux depuis le démarrage, relevés à chaque enregistrement.
+#[derive(Clone, Copy, Default)]
+struct Totals {
+    counts: Counts,
+    drops: u64,
+    claims: u64,
+}
+
+impl Totals {
+    fn now() -> Self {
+        Self {
+            counts: STATS.snapshot().lifetime,
+            drops: METRICS.drops_detected.load(Ordering::Relaxed),
+            claims: METRICS.claims_detected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Bilan de la journée en cours depuis le relevé `last`.
+fn rollup_since(last: &Totals, current: &Totals, day: NaiveDate, tz: Tz, names: &UsernameMap, windows: &WindowStore) -> Rollup {
+    let start = day_start(day, tz);
+    let (windows_opened, windows_closed) = windows
+        .count_between(start, day_start(day + Duration::days(1), tz))
+        .unwrap_or_else(|e| {
+            error!(error = %e, "comptage des fenêtres du jour impossible");
+            (0, 0)
+        });
+    Rollup {
+        counts: current.counts.delta(&last.counts),
+        drops_detected: current.drops.saturating_sub(last.drops),
+        claims_observed: current.claims.saturating_sub(last.claims),
+        windows_opened,
+        windows_closed,
+        names_total: names.len() as u64,
+        names_covered: names.iter().filter(|entry| entry.value().last_seen().is_some_and(|seen| seen >= start)).count() as u64,
+    }
+}
+
+/// Enregistre les compteurs du jour à intervalle régulier et envoie les
+/// résumés échus. Un arrêt perd au plus le dernier intervalle.
+pub fn spawn_reports(
+    store: Arc<RollupStore>,
+    windows: Arc<WindowStore>,
+    names: Arc<UsernameMap>,
+    dispatcher: Arc<Dispatcher>,
+    config: ReportsConfig,
+    tz: Tz,
+) {
+    tokio::spawn(async move {
+        // compteurs partis de zéro au lancement du scanner
+        let mut last = Totals::default();
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(config.flush_interval_secs.max(1))).await;
+            let now = Utc::now();
+            let current = Totals::now();
+            let day = now.with_timezone(&tz).date_naive();
+            // lectures et écritures SQLite hors des threads du runtime
+            let (flushed, due) = {
+                let (store, windows, names, config) = (store.clone(), windows.clone(), names.clone(), config.clone());
+                tokio::task::spawn_blocking(move || {
+                    let flushed = store.accumulate(day, &rollup_since(&last, &current, day, tz, &names, &windows));
+                    (flushed, due_reports(&store, &config, tz, now))
+                })
+                .await
+                .expect("bilan du jour interrompu")
+            };
+            match flushed {
+                Ok(()) => {
+                    debug!(day = %day, "bilan du jour enregistré");
+                    last = current;
+                }
+                Err(e) => error!(error = %e, "bilan du jour non enregistré"),
+            }
+            match due {
+                Ok(events) => {
+                    for event in events {
+                        dispatcher.emit(event).await;
+                    }
+                }
+                Err(e) => error!(error = %e, "planificateur des résumés"),
+            }
+        }
+    });
+}
+
+#[test]
+fn test_rollups_and_due_reports() {
+    use super::storage::open_memory_db;
+
+    let store = RollupStore::open(open_memory_db().unwrap()).unwrap();
+    let tz = chrono_tz::Europe::Paris;
+    let day = |d: u32| NaiveDate::from_ymd_opt(2025, 7, d).unwrap();
+    let delta = |ok: u64, errors: u64, covered: u64| Rollup {
+        counts: Counts { ok, errors, batches_ok: ok, names_checked: ok * 10, ..Default::default() },
+        drops_detected: 1,
+        names_total: 100,
+        names_covered: covered,
+        ..Default::default()
+    };
+
+    // compteurs cumulés, relevés remplacés
+    store.accumulate(day(13), &delta(80, 20, 40)).unwrap();
+    store.accumulate(day(13), &delta(10, 10, 90)).unwrap();
+    store.accumulate(day(14), &delta(50, 0, 100)).unwrap();
+    let days = store.days(day(13), day(13)).unwrap();
+    assert_eq!(days[0].rollup.counts.ok, 90);
+    assert_eq!(days[0].rollup.drops_detected, 2);
+    assert_eq!(days[0].rollup.names_covered, 90);
+    assert_eq!(days[0].rollup.success_pct(), 75.0);
+    assert!(days[0].csv_row().starts_with("2025-07-13,120,90,0,0,0,30,0,75.0,90,0,900,2,"));
+
+    let week = store.summary(Period::Weekly, day(14)).unwrap();
+    assert_eq!((week.from, week.days.len()), (day(8), 2));
+    assert_eq!(week.totals.counts.requests(), 170);
+    assert_eq!(week.totals.coverage_pct(), 95.0);
+
+    // lundi 14 juillet 2025 à Paris : à 07h59, les résumés précédents (12 juillet,
+    // semaine au 6) n'ont pas de données ; à 08h00, veille puis semaine, une seule fois
+    let at = |rfc: &str| DateTime::parse_from_rfc3339(rfc).unwrap().with_timezone(&Utc);
+    let config = ReportsConfig::default();
+    assert!(due_reports(&store, &config, tz, at("2025-07-14T07:59:00+02:00")).unwrap().is_empty());
+    let sent = due_reports(&store, &config, tz, at("2025-07-14T08:00:00+02:00")).unwrap();
+    let periods: Vec<_> = sent.iter().map(|e| match e {
+        Event::Report { summary } => (summary.period, summary.from, summary.to),
+        _ => unreachable!(),
+    }).collect();
+    assert_eq!(periods, vec![(Period::Daily, day(13), day(13)), (Period::Weekly, day(7), day(13))]);
+    assert!(due_reports(&store, &config, tz, at("2025-07-14T12:00:00+02:00")).unwrap().is_empty());
+}
@@ -1,4 +1,4 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rand::seq::IndexedRandom;
 use reqwest::Client;
 use serde_json::json;
@@ -6,11 +6,17 @@ use serde::Deserialize;
 use rand_chacha::ChaCha12Rng;
 use rand::SeedableRng;
 use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::Semaphore;
+use tokio::time::{timeout, Duration};
+use tracing::{debug, instrument, warn};
+use crate::utilities::clock::CLOCK;
+use crate::utilities::failures::{ErrorClass, Failure};
 use crate::utilities::sql_management::UsernameResult;
+use crate::utilities::stats::{Outcome, STATS};
+use crate::utilities::username::Username;
 
 
-const MOJANG_POST_URL: &str = "https://api.mojang.com/profiles/minecraft";
-
 const AGENTS: &[&str] = &[
     "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36",
     "Mozilla/5.0 (Macintosh; Intel Mac OS X 13_2_1) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.1 Safari/605.1.15",
@@ -40,17 +46,51 @@ struct MojangResponse {
 
 
 
+/// Réponse de l'API pour un batch.
+#[derive(Debug, Default)]
+pub struct BatchResponse {
+    pub success: bool,
+    pub results: Vec<UsernameResult>,
+    pub status: usize,
+    /// Corps de la réponse quand l'API refuse le batch (400).
+    pub detail: Option<String>,
+    /// Échec hors refus de l'API ; `status` vaut 0 sans réponse HTTP.
+    pub failure: Option<Failure>,
+}
+
+impl BatchResponse {
+    fn failed(status: usize) -> Self {
+        Self { status, ..Default::default() }
+    }
+
+    fn error(status: usize, failure: Failure) -> Self {
+        Self { status, failure: Some(failure), ..Default::default() }
+    }
+}
+
+/// Batch de la boucle principale : une liste vide n'est acceptée que pour
+/// un seul pseudo (voir [`parse_batch`]).
 pub async fn fetch_batch(
     client: &Client,
-    usernames: &[String],
-) -> Result<(bool, Vec<UsernameResult>,usize), Box<dyn std::error::Error + Send + Sync>>{
+    usernames: &[Username],
+) -> Result<BatchResponse, Box<dyn std::error::Error + Send + Sync>> {
+    fetch_with(client, usernames, usernames.len() == 1).await
+}
+
+#[instrument(level = "debug", skip_all, fields(size = usernames.len()))]
+async fn fetch_with(
+    client: &Client,
+    usernames: &[Username],
+    allow_empty: bool,
+) -> Result<BatchResponse, Box<dyn std::error::Error + Send + Sync>>{
 
     let user_agent = AGENTS.choose(&mut rand::rng()).unwrap();
     let mut rng = ChaCha12Rng::from_rng(&mut rand::rng());
     let url = format!("https://api.minecraftservices.com{}", PATH.choose(&mut rng).unwrap());
 
 
-    let body = json!(usernames);
+    let body = json!(usernames.iter().map(Username::display).collect::<Vec<_>>());
+    let sent = Utc::now();
     match client
         .post(url)
         .header("User-Agent", *user_agent)
@@ -60,49 +100,270 @@ pub async fn fetch_batch(
         .await
     {
         Ok(resp) => {
-            if resp.status() == 200 {
-                let now = Utc::now();
-                let result = resp.json::<Vec<MojangResponse>>().await.unwrap_or_default();
-                let n = result.len();
-                let uuid_map = result
-                    .into_iter()
-                    .map(|r| (r.name.to_lowercase(), r.id))
-                    .collect::<HashMap<_, _>>();
-    
-                let mapped = usernames
-                    .iter()
-                    .map(|name| UsernameResult {
-                        username: name.clone(),
-                        uuid: uuid_map.get(&name.to_lowercase()).cloned(),
-                        last_seen : now.to_string(),
-                    })
-                    .collect();
-                if n!=0{
-                    return Ok((true, mapped,200)); // succès
+            let received = Utc::now();
+            CLOCK.observe(resp.headers(), sent, received);
+            let status = resp.status().as_u16() as usize;
+            match status {
+                200 => {
+                    let now = CLOCK.corrected(received);
+                    // corps lu en entier : gardé comme échantillon s'il est illisible
+                    let raw = match resp.text().await {
+                        Ok(raw) => raw,
+                        Err(e) => return Ok(BatchResponse::error(200, Failure::from_reqwest(&e))),
+                    };
+                    match parse_batch(usernames, &raw, now, allow_empty) {
+                        Ok(mapped) => Ok(BatchResponse { success: true, results: mapped, status: 200, ..Default::default() }), // succès
+                        Err(failure) => Ok(BatchResponse::error(200, failure)),
+                    }
                 }
-                else {
-                    return Ok((false, vec![], 699)); // pas de résultats
+                429 => Ok(BatchResponse::failed(429)), // trop de requêtes
+                403 => Ok(BatchResponse::failed(403)), // accès interdit
+                400 => {
+                    // un ou plusieurs pseudos refusés : l'appelant bissecte le batch
+                    let detail = resp.text().await.unwrap_or_default();
+                    Ok(BatchResponse { status: 400, detail: Some(detail), ..Default::default() })
                 }
-            } else {
-                if resp.status() == 429 {
-                    return Ok((false, vec![], 429)); // trop de requêtes
+                _ => {
+                    let class = if resp.status().is_server_error() {
+                        ErrorClass::ServerError
+                    } else {
+                        warn!(status, "statut inattendu de l'API Mojang");
+                        ErrorClass::UnexpectedStatus
+                    };
+                    let body = resp.text().await.unwrap_or_default();
+                    Ok(BatchResponse::error(status, Failure::new(class, format!("{status} : {body}"))))
                 }
-                if resp.status() == 403 {
-                    return Ok((false, vec![], 403)); // accès interdit
-                }
-                if resp.status() == 400{
-                    println!("ERREUR USERNAME : {:?}", usernames);
-                }
-                else 
-                {
-                    println!("ERREUR MOJANG : {:?}", resp.status());
+            }
+        }
+        Err(e) => {
+            debug!(error = %e, "erreur réseau");
+            Ok(BatchResponse::error(0, Failure::from_reqwest(&e)))
+        }
+    }
+}
+
+/// Corps d'une réponse 200 → état de chaque pseudo du batch.
+///
+/// Une liste vide dirait qu'aucun pseudo du batch n'est pris ; sur un batch
+/// complet c'est plus souvent un proxy qui renvoie un faux 200 : elle est
+/// refusée, sauf si `allow_empty` (un seul pseudo, ou sous-batch de la
+/// bissection, où un 200 vide départage les moitiés).
+fn parse_batch(usernames: &[Username], raw: &str, now: DateTime<Utc>, allow_empty: bool) -> Result<Vec<UsernameResult>, Failure> {
+    let result = serde_json::from_str::<Vec<MojangResponse>>(raw)
+        .map_err(|e| Failure::new(ErrorClass::Decode, format!("{e} : {raw}")))?;
+    if result.is_empty() && !allow_empty {
+        return Err(Failure::new(ErrorClass::EmptyResult, raw)); // pas de résultats
+    }
+    let mut uuid_map = result
+        .into_iter()
+        .map(|r| (Username::key_of(&r.name), r))
+        .collect::<HashMap<_, _>>();
+
+    Ok(usernames
+        .iter()
+        .map(|name| {
+            let found = uuid_map.remove(name.key());
+            UsernameResult {
+                username: name.clone(),
+                uuid: found.as_ref().map(|r| r.id.clone()),
+                last_seen : now,
+                canonical: found.map(|r| r.name),
+            }
+        })
+        .collect())
+}
+
+type Fetched = Result<Result<BatchResponse, Box<dyn std::error::Error + Send + Sync>>, tokio::time::error::Elapsed>;
+
+/// Résultat d'un appel à [`fetch_batch`] borné par `timeout`.
+pub fn outcome_of(fetched: &Fetched) -> Outcome {
+    match fetched {
+        Ok(Ok(response)) => match &response.failure {
+            Some(failure) => failure.class.outcome(),
+            None => Outcome::from_status(response.status),
+        },
+        Ok(Err(_)) => Outcome::Error,
+        Err(_) => Outcome::Timeout,
+    }
+}
+
+/// Échec d'un appel à [`fetch_batch`] borné par `timeout`, hors refus de l'API.
+pub fn failure_of(fetched: &Fetched) -> Option<Failure> {
+    match fetched {
+        Ok(Ok(response)) => response.failure.clone(),
+        Ok(Err(e)) => Some(Failure::new(ErrorClass::Network, e.to_string())),
+        Err(e) => Some(Failure::new(ErrorClass::Timeout, format!("borne autour de la requête : {e}"))),
+    }
+}
+
+/// Compte la requête dans [`STATS`] (résultat et classe d'échec) et renvoie son résultat.
+pub fn record_fetch(fetched: &Fetched) -> Outcome {
+    let outcome = outcome_of(fetched);
+    STATS.record_request(outcome);
+    if let Some(failure) = failure_of(fetched) {
+        STATS.record_failure(&failure);
+    }
+    outcome
+}
+
+/// Résultat de la bissection d'un batch refusé en 400.
+#[derive(Debug, Default)]
+pub struct BisectOutcome {
+    /// Résultats des sous-batchs acceptés.
+    pub results: Vec<UsernameResult>,
+    /// Pseudos isolés que l'API refuse, avec le corps de la réponse.
+    pub invalid: Vec<(Username, String)>,
+    /// Pseudos dont aucun sous-batch n'a abouti (réessayés au prochain tour).
+    pub unresolved: Vec<Username>,
+    /// Nombre de requêtes envoyées pendant la bissection.
+    pub requests: usize,
+}
+
+/// Coupe récursivement en deux un batch refusé en 400 jusqu'à isoler les
+/// pseudos fautifs ; les moitiés acceptées sont traitées normalement.
+pub async fn bisect_batch(
+    clients: &[Client],
+    semaphore: &Semaphore,
+    rng: &mut ChaCha12Rng,
+    usernames: &[Username],
+    max_retries: usize,
+) -> BisectOutcome {
+    bisect_with(usernames, max_retries, |chunk| {
+        let client = clients.choose(rng).cloned();
+        async move {
+            let client = client?;
+            let _permit = semaphore.acquire().await.expect("Semaphore closed unexpectedly");
+            Some(timeout(Duration::from_secs(5), fetch_with(&client, &chunk, true)).await)
+        }
+    })
+    .await
+}
+
+/// Bissection avec `fetch` pour envoyer un sous-batch ; `None` si aucun
+/// client n'est disponible.
+async fn bisect_with<F, Fut>(usernames: &[Username], max_retries: usize, mut fetch: F) -> BisectOutcome
+where
+    F: FnMut(Vec<Username>) -> Fut,
+    Fut: Future<Output = Option<Fetched>>,
+{
+    let mut outcome = BisectOutcome::default();
+    let mut pending: Vec<Vec<Username>> = Vec::new();
+
+    // le batch complet est déjà connu comme invalide : on démarre par ses moitiés
+    if usernames.len() == 1 {
+        outcome.invalid.push((usernames[0].clone(), "400".to_string()));
+        return outcome;
+    }
+    let (left, right) = usernames.split_at(usernames.len() / 2);
+    pending.push(right.to_vec());
+    pending.push(left.to_vec());
+
+    while let Some(chunk) = pending.pop() {
+        let mut resolved = false;
+        for _retry in 0..max_retries {
+            let fetched = match fetch(chunk.clone()).await {
+                Some(fetched) => fetched,
+                None => break,
+            };
+            outcome.requests += 1;
+            record_fetch(&fetched);
+            let response = match fetched {
+                Ok(Ok(response)) => response,
+                _ => continue,
+            };
+            if response.success {
+                outcome.results.extend(response.results);
+                resolved = true;
+                break;
+            }
+            if response.status == 400 {
+                if chunk.len() == 1 {
+                    let reason = match response.detail.as_deref() {
+                        Some(detail) if !detail.is_empty() => format!("400 : {detail}"),
+                        _ => "400".to_string(),
+                    };
+                    outcome.invalid.push((chunk[0].clone(), reason));
+                } else {
+                    let (left, right) = chunk.split_at(chunk.len() / 2);
+                    pending.push(right.to_vec());
+                    pending.push(left.to_vec());
                 }
-                return Ok((false, vec![],699)); // autre statut
+                resolved = true;
+                break;
             }
         }
-        Err(_) => {
-        	// println!("{:?}",x);
-            return Ok((false, vec![],699)); // erreur réseau
+        if !resolved {
+            outcome.unresolved.extend(chunk);
+        }
+    }
+
+    outcome
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_bisect_batch() {
+    let names = |raw: &[&str]| raw.iter().map(|n| Username::new(n)).collect::<Vec<_>>();
+    let keys = |names: &[Username]| names.iter().map(|n| n.key().to_string()).collect::<Vec<_>>();
+    let now = Utc::now();
+    // faux serveur : 400 si le sous-batch contient un pseudo invalide, erreur
+    // réseau pour `Flaky`, sinon la liste des pseudos pris (vide si aucun)
+    let fake = |chunk: Vec<Username>| async move {
+        if chunk.iter().any(|n| n.key().contains(' ')) {
+            return Some(Ok(Ok(BatchResponse { status: 400, detail: Some("invalid name".to_string()), ..Default::default() })));
+        }
+        if chunk.iter().any(|n| n.key() == "flaky") {
+            return Some(Ok(Err("connexion coupée".into())));
         }
+        let taken: Vec<_> = chunk
+            .iter()
+            .filter(|n| n.key() == "dream")
+            .map(|n| json!({ "id": "ec70bcaf702f4bb8b48d276fa52a780c", "name": n.display() }))
+            .collect();
+        let raw = serde_json::to_string(&taken).unwrap();
+        let results = parse_batch(&chunk, &raw, now, true).unwrap();
+        Some(Ok(Ok(BatchResponse { success: true, results, status: 200, ..Default::default() })))
+    };
+
+    // [libre1, libre2] → 200 vide ; [bad name, Dream] → 400 → [bad name] + [Dream]
+    let outcome = bisect_with(&names(&["libre1", "libre2", "bad name", "Dream"]), 3, fake).await;
+    let invalid: Vec<_> = outcome.invalid.iter().map(|(n, reason)| (n.key().to_string(), reason.as_str())).collect();
+    assert_eq!(invalid, vec![("bad name".to_string(), "400 : invalid name")]);
+    let results: Vec<_> = outcome.results.iter().map(|r| (r.username.key().to_string(), r.uuid.is_some())).collect();
+    assert_eq!(results, vec![("libre1".to_string(), false), ("libre2".to_string(), false), ("dream".to_string(), true)]);
+    assert!(outcome.unresolved.is_empty());
+    assert_eq!(outcome.requests, 4);
+
+    // un sous-batch toujours en erreur est rendu après `max_retries` essais
+    let outcome = bisect_with(&names(&["Flaky", "libre3"]), 3, fake).await;
+    assert_eq!(keys(&outcome.unresolved), vec!["flaky"]);
+    assert_eq!(outcome.results.len(), 1);
+    assert_eq!(outcome.requests, 4);
+}
+
+#[test]
+fn test_empty_result_on_full_batch() {
+    use crate::utilities::sql_management::{insert_name, update_batch_status, UsernameMap, WindowMap};
+
+    let now = Utc::now();
+    let batch: Vec<Username> = ["Dream", "Notch"].iter().map(|n| Username::new(n)).collect();
+    let map = UsernameMap::default();
+    for name in &batch {
+        insert_name(&map, &name.display());
+        let mut state = map.get_mut(name.key()).unwrap();
+        state.set_uuid(Some("ec70bcaf702f4bb8b48d276fa52a780c"));
+        state.set_last_seen(now - chrono::Duration::hours(1));
     }
+
+    // faux 200 vide sur un batch complet : échec, aucun pseudo libéré
+    let failure = parse_batch(&batch, "[]", now, false).unwrap_err();
+    assert_eq!(failure.class, ErrorClass::EmptyResult);
+    let events = match parse_batch(&batch, "[]", now, false) {
+        Ok(results) => update_batch_status(&map, &results, &WindowMap::default()),
+        Err(_) => Vec::new(),
+    };
+    assert!(events.is_empty());
+    assert!(map.get("dream").unwrap().has_uuid());
+
+    // un seul pseudo, ou sous-batch de bissection : la liste vide vaut libre
+    assert_eq!(parse_batch(&batch[..1], "[]", now, true).unwrap()[0].uuid, None);
 }
@@ -1,6 +1,4 @@
-use anyhow::Result;
-use serde::Deserialize;
-use std::error::Error;
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path};
@@ -10,9 +8,13 @@ use dashmap::DashMap;            // map concurrente shardée
 
 
 use chrono::{DateTime, Duration, Utc};
-use chrono_tz::Europe::Paris;
+use compact_str::CompactString;
+use std::num::NonZeroU128;
+use tracing::{error, info, instrument, warn};
 
-use super::log_and_errors::notify_drop_window;
+use super::notifiers::Event;
+use super::tags::{TagSet, TAGS};
+use super::username::{apply_case, case_mask, Username};
 
 pub const BATCH_SIZE: usize = 10;
 
@@ -25,9 +27,11 @@ struct MojangResponse {
 
 #[derive(Debug)]
 pub struct UsernameResult {
-    pub username: String,
+    pub username: Username,
     pub uuid: Option<String>,
-    pub last_seen: String,
+    pub last_seen: DateTime<Utc>,
+    /// Casse officielle renvoyée par l'API (champ `name`), si le pseudo est pris.
+    pub canonical: Option<String>,
 }
 
 pub struct UsernameEntry {
@@ -36,15 +40,126 @@ pub struct UsernameEntry {
     pub last_seen: String,
 }
 
+/// État connu d'un pseudo suivi, sous forme compacte (32 octets, sans
+/// allocation) : l'UUID tient dans un `u128`, l'horodatage en microsecondes
+/// Unix dans un `i64`, la casse officielle dans un masque (voir [`case_mask`])
+/// et les tags dans un [`TagSet`].
+#[derive(Debug, Clone)]
+pub struct NameState {
+    uuid: Option<NonZeroU128>,
+    last_seen: i64,
+    /// Dernière casse officielle vue dans la réponse de l'API.
+    canonical_case: u32,
+    tags: TagSet,
+}
+
+const NEVER_SEEN: i64 = i64::MIN;
+/// Aucune casse officielle vue ; impossible pour un vrai pseudo (16 caractères au plus).
+const NO_CANONICAL: u32 = u32::MAX;
+
+impl Default for NameState {
+    fn default() -> Self {
+        Self { uuid: None, last_seen: NEVER_SEEN, canonical_case: NO_CANONICAL, tags: TagSet::default() }
+    }
+}
+
+impl NameState {
+    pub fn has_uuid(&self) -> bool {
+        self.uuid.is_some()
+    }
+
+    /// UUID au format de l'API (32 caractères hexadécimaux, sans tirets).
+    pub fn uuid(&self) -> Option<String> {
+        self.uuid.map(|id| format!("{:032x}", id.get()))
+    }
+
+    /// Enregistre l'UUID renvoyé par l'API ; `false` s'il n'est pas lisible.
+    pub fn set_uuid(&mut self, uuid: Option<&str>) -> bool {
+        match uuid {
+            None => {
+                self.uuid = None;
+                true
+            }
+            Some(raw) => match parse_uuid(raw) {
+                Some(id) => {
+                    self.uuid = Some(id);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    pub fn last_seen(&self) -> Option<DateTime<Utc>> {
+        if self.last_seen == NEVER_SEEN {
+            return None;
+        }
+        DateTime::from_timestamp_micros(self.last_seen)
+    }
+
+    pub fn set_last_seen(&mut self, at: DateTime<Utc>) {
+        self.last_seen = at.timestamp_micros();
+    }
+
+    /// Casse officielle du pseudo `name` (la clé de l'entrée), si déjà vue.
+    pub fn canonical(&self, name: &Username) -> Option<String> {
+        (self.canonical_case != NO_CANONICAL).then(|| apply_case(name.key(), self.canonical_case).into_owned())
+    }
+
+    pub fn set_canonical(&mut self, name: &str) {
+        self.canonical_case = case_mask(name);
+    }
+
+    pub fn tags(&self) -> TagSet {
+        self.tags
+    }
+
+    pub fn add_tags(&mut self, tags: TagSet) {
+        self.tags = self.tags.union(tags);
+    }
+}
+
+/// UUID Mojang (avec ou sans tirets) → entier ; l'UUID nul n'est jamais un profil.
+pub fn parse_uuid(raw: &str) -> Option<NonZeroU128> {
+    let hex: CompactString = raw.chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 {
+        return None;
+    }
+    u128::from_str_radix(&hex, 16).ok().and_then(NonZeroU128::new)
+}
+
+pub type UsernameMap = DashMap<Username, NameState, RandomState>;
+
+/// Fenêtre pendant laquelle un pseudo libéré redevient disponible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DropWindow {
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Ligne de `drop_windows.txt`, que les anciennes versions tenaient à côté
+/// de la base ; lu seulement pour l'import initial (voir `WindowStore::import_if_empty`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropWindowRecord {
+    pub username: String,
+    #[serde(flatten)]
+    pub window: DropWindow,
+}
+
+pub const DROP_WINDOWS_PATH: &str = "drop_windows.txt";
+
+/// pseudo → fenêtre de drop
+pub type WindowMap = DashMap<Username, DropWindow, RandomState>;
+
 
 pub fn init_hashmap_from_txt(
     file_path: &str,
-) -> std::io::Result<DashMap<String, (Option<String>, Option<String>), RandomState>> {
+) -> std::io::Result<UsernameMap> {
     let path = Path::new(file_path);
 
     // ─── Map vide si le fichier n’existe pas ────────────────────────────
     if !path.exists() {
-        eprintln!("❌ Fichier introuvable : {file_path}");
+        error!(path = file_path, "liste de pseudos introuvable");
         return Ok(DashMap::with_capacity_and_hasher_and_shard_amount(
             0,
             RandomState::new(),
@@ -52,103 +167,106 @@ pub fn init_hashmap_from_txt(
         ));
     }
     // ─── Pré-allocation + hasher rapide ─────────────────────────────────
-    let map: DashMap<String, (Option<String>, Option<String>), RandomState> =
+    let map: UsernameMap =
         DashMap::with_capacity_and_hasher_and_shard_amount(70_000, RandomState::new(), 1_024);
 
     // ─── Lecture ligne par ligne ────────────────────────────────────────
     let reader = BufReader::new(File::open(path)?);
     for line in reader.lines() {
-        let name = line?.trim().to_string();
-        if !name.is_empty() {
-            // valeur initiale : (uuid = None, last_seen = None)
-            map.insert(name, (None, None));
-        }
+        insert_name(&map, &line?);
     }
 
     Ok(map)
 }
 
+/// Ajoute un pseudo brut à la map ; une même clé en plusieurs casses n'est
+/// suivie qu'une fois (la première). Renvoie `false` si rien n'a été ajouté.
+pub fn insert_name(map: &UsernameMap, raw: &str) -> bool {
+    let name = Username::new(raw);
+    if name.key().is_empty() || map.contains_key(name.key()) {
+        return false;
+    }
+    map.entry(name).or_default();
+    true
+}
+
+/// Relit les fenêtres consignées dans `drop_windows.txt` (lignes illisibles ignorées).
+pub fn load_drop_windows(file_path: &str) -> std::io::Result<Vec<DropWindowRecord>> {
+    let path = Path::new(file_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        match serde_json::from_str::<DropWindowRecord>(&line) {
+            Ok(record) => records.push(record),
+            Err(_) if line.trim().is_empty() => {}
+            Err(e) => warn!(error = %e, line = %line, "fenêtre illisible"),
+        }
+    }
+    Ok(records)
+}
+
+/// Met à jour la map avec les résultats d'un batch et renvoie les événements
+/// détectés (fenêtres de drop, prises), à transmettre au `Dispatcher`.
+#[instrument(level = "debug", skip_all, fields(results = batch_results.len()))]
 pub fn update_batch_status(
-    map: &DashMap<String, (Option<String>, Option<String>), ahash::RandomState>,
+    map: &UsernameMap,
     batch_results: &[UsernameResult],
-    map_windows: &DashMap<String, (String, String), ahash::RandomState>, // aussi thread-safe
-) -> Result<(), Box<dyn std::error::Error>> {
+    map_windows: &WindowMap, // aussi thread-safe
+) -> Vec<Event> {
+    let mut events = Vec::new();
     for entry in batch_results {
-        let username = entry.username.to_lowercase();
-        let uuid      = entry.uuid.clone();
-        let last_seen = entry.last_seen.clone();
-
-        if let Some(mut guard) = map.get_mut(&username) {
-            // guard : verrou sur le shard ⇒ mutation safe
-            if guard.0.is_some() && uuid.is_none() {
-                println!("feur : {} a perdu son UUID", username);
-                // unwrap() sûr, car guard.1 doit déjà être Some(timestamp)
-                if let Some(prev_ts) = guard.1.as_deref() {
-                    get_drop_window(&username, prev_ts, &last_seen, map_windows)?;
-                }
+        // pseudo retiré du suivi pendant la requête : résultat ignoré
+        let Some(mut guard) = map.get_mut(entry.username.key()) else {
+            continue;
+        };
+        // guard : verrou sur le shard ⇒ mutation safe
+        if guard.has_uuid() && entry.uuid.is_none() {
+            // la casse officielle est perdue avec le compte : on garde la dernière connue
+            let shown = guard.canonical(&entry.username).unwrap_or_else(|| entry.username.display().into_owned());
+            info!(username = %shown, "UUID perdu");
+            // last_seen doit déjà être Some(timestamp) puisque l'UUID a été vu
+            if let Some(prev_ts) = guard.last_seen() {
+                let window = get_drop_window(&entry.username, prev_ts, entry.last_seen, map_windows);
+                events.push(Event::DropWindow { username: shown, window, tags: TAGS.names(guard.tags()) });
+            }
+        }
+        if !guard.has_uuid() && entry.uuid.is_some() {
+            // repris pendant (ou après) sa fenêtre : les rappels n'ont plus lieu d'être
+            if let Some((_, window)) = map_windows.remove(entry.username.key()) {
+                let shown = entry.canonical.clone().unwrap_or_else(|| entry.username.display().into_owned());
+                events.push(Event::Claimed { username: shown, window, tags: TAGS.names(guard.tags()) });
             }
-            guard.0 = uuid;
-            guard.1 = Some(last_seen);
-            // guard droppe ici ⇒ verrou libéré
-        } else {
-            map.insert(username, (uuid, Some(last_seen)));
         }
+        if !guard.set_uuid(entry.uuid.as_deref()) {
+            warn!(username = %entry.username, uuid = ?entry.uuid, "UUID illisible");
+        }
+        guard.set_last_seen(entry.last_seen);
+        if let Some(canonical) = entry.canonical.as_deref() {
+            guard.set_canonical(canonical);
+        }
+        // guard droppe ici ⇒ verrou libéré
     }
-    Ok(())
+    events
 }
 
 pub fn get_drop_window(
-    username: &str,
-    last_req_time_iso: &str,
-    lost_at_iso: &str,
-    map_windows: &DashMap<String, (String, String), ahash::RandomState>,
-) -> Result<(), Box<dyn Error>> {
-    // 🔹 Conversion des timestamps ISO en chrono::DateTime<Utc>
-    let lost_at_utc: DateTime<Utc> = lost_at_iso.parse()?; // pars l'ISO avec le décalage
-    let lost_at = lost_at_utc.with_timezone(&Paris);       // convertit vers UTC+2
-
-    let last_req_utc: DateTime<Utc> = last_req_time_iso.parse()?;
-    let last_req_time = last_req_utc.with_timezone(&Paris);
-
+    username: &Username,
+    last_req_time: DateTime<Utc>,
+    lost_at: DateTime<Utc>,
+    map_windows: &WindowMap,
+) -> DropWindow {
     // 🔹 Calcul de la fenêtre de snipe (début + fin)
-    let snipe_window_beginning = last_req_time + Duration::days(37);
-    let snipe_window_end = lost_at + Duration::days(37);
-
-    // 🔹 Envoi webhook
-    let username_clone = username.to_string();
-    tokio::spawn(async move {
-        if let Err(_) = notify_drop_window(&username_clone, &snipe_window_beginning.to_rfc3339(), &snipe_window_end.to_rfc3339()).await {
-            eprintln!("ERREUR ENVOIE WEBHOOK @everyone");
-        }
-    });
-
-    map_windows.insert(
-        username.to_string(),
-        (
-            snipe_window_beginning.to_rfc3339(),
-            snipe_window_end.to_rfc3339(),
-        ),
-    );
-    // write it in a .txt in a common drop windows txt in case webhook fails
-    let username = username.to_string();
-    let begin = snipe_window_beginning.to_rfc3339();
-    let end = snipe_window_end.to_rfc3339();
-
-    tokio::task::spawn_blocking(move || -> std::io::Result<()> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open("drop_windows.txt")?;
+    let window = DropWindow {
+        begin: last_req_time + Duration::days(37),
+        end: lost_at + Duration::days(37),
+    };
 
-        writeln!(file, "{{\"username\": \"{}\", \"begin\": \"{}\", \"end\": \"{}\"}}", username, begin, end)?;
-        Ok(())
-    });
-
-
-    Ok(())
+    map_windows.insert(username.clone(), window);
+    window
 }
 
 
@@ -386,13 +504,15 @@ pub fn get_drop_window(
 
 #[test]
 fn test_init_hashmap_from_txt() {
-    
+    use std::io::Write;
 
-    let path = "C:/Users/cinq2/Documents/claimer_rs_full/feur.txt";
-    if !Path::new(path).exists() {
-        panic!("Test file not found: {}", path);
-    }
-    let map = init_hashmap_from_txt(path).expect("should initialize hashmap from txt");
+    let path = std::env::temp_dir().join(format!("feur_{}.txt", std::process::id()));
+    let mut file = File::create(&path).expect("should create test file");
+    writeln!(file, "Dream\nnotch\n\n  jeb_  \nDREAM").unwrap();
+    drop(file);
+
+    let map = init_hashmap_from_txt(path.to_str().unwrap()).expect("should initialize hashmap from txt");
+    std::fs::remove_file(&path).unwrap();
 
 
     println!("Map contents:");
@@ -400,71 +520,104 @@ fn test_init_hashmap_from_txt() {
         let (key, value) = entry.pair();
         println!("{}: {:?}", key, value);
     }
-    for mut entry in map.iter_mut() {
-        let (_, value_mut) = entry.pair_mut();
-        // modify the value
-        let (uuid, last_seen) = value_mut;
-        let new_value = (uuid.clone(), last_seen.clone());
-        *value_mut = new_value;
-    }
-    println!("Map size: {}", map.len());
+    // "Dream" et "DREAM" ne forment qu'une entrée, casse de la première ligne conservée
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get("dream").unwrap().key().display(), "Dream");
+    assert!(map.get("jeb_").is_some());
 
 
 }
 
+#[cfg(test)]
+const UUID_0: &str = "069a79f444e94726a5befca90e38aaf5";
+#[cfg(test)]
+const UUID_1: &str = "853c80ef-3c37-49fd-aa49-938b674adae6";
+
+#[test]
+fn test_name_state_is_compact() {
+    assert_eq!(std::mem::size_of::<NameState>(), 32);
+    assert_eq!(std::mem::size_of::<Username>(), 32);
+
+    let mut state = NameState::default();
+    assert!(state.last_seen().is_none());
+    assert!(!state.set_uuid(Some("uuid-0")));
+    assert!(state.set_uuid(Some(UUID_0)));
+    assert_eq!(state.uuid().as_deref(), Some(UUID_0));
+}
+
 #[tokio::test(flavor = "current_thread")]     // runtime Tokio dédié au test
 async fn test_update_batch_status() -> Result<(), Box<dyn std::error::Error>> {
     // ───── Map principale pré-remplie ────────────────────────────────
-    let users: DashMap<String, (Option<String>, Option<String>), RandomState> =
+    let users: UsernameMap =
         DashMap::with_capacity_and_hasher_and_shard_amount(16, RandomState::new(), 16);
 
-    users.insert(
-        "dream".into(),
-        (Some("uuid-0".into()), Some(Utc::now().to_rfc3339())),
-    );
-    users.insert(
-        "notch".into(),
-        (None, Some(Utc::now().to_rfc3339())),
-    );
+    let mut dream = NameState::default();
+    dream.set_uuid(Some(UUID_0));
+    dream.set_last_seen(Utc::now());
+    users.insert("Dream".into(), dream);
+    let mut notch = NameState::default();
+    notch.set_last_seen(Utc::now());
+    users.insert("notch".into(), notch);
 
     // ───── Map des fenêtres de drop ──────────────────────────────────
-    let drop_windows: DashMap<String, (String, String), RandomState> =
+    let drop_windows: WindowMap =
         DashMap::with_capacity_and_hasher_and_shard_amount(4, RandomState::new(), 4);
 
     // ───── 1ʳᵉ vague de résultats : Dream obtient un nouvel UUID ─────
     let batch1 = vec![
         UsernameResult {
-            username: "dream".into(),
-            uuid: Some("uuid-1".into()),
-            last_seen: Utc::now().to_rfc3339(),
+            username: "DREAM".into(),
+            uuid: Some(UUID_1.into()),
+            last_seen: Utc::now(),
+            canonical: Some("Dream".into()),
         },
         UsernameResult {
             username: "notch".into(),
             uuid: None,
-            last_seen: Utc::now().to_rfc3339(),
+            last_seen: Utc::now(),
+            canonical: None,
         },
     ];
-    update_batch_status(&users, &batch1, &drop_windows)?;
+    update_batch_status(&users, &batch1, &drop_windows);
 
+    // la casse du batch ne crée pas de doublon
+    assert_eq!(users.len(), 2);
     assert_eq!(
-        users.get("dream").unwrap().0,
-        Some("uuid-1".to_string())
+        users.get("dream").unwrap().uuid(),
+        Some(UUID_1.replace('-', ""))
     );
-    assert!(users.get("notch").unwrap().0.is_none());
+    let dream_key = Username::new("dream");
+    assert_eq!(users.get("dream").unwrap().canonical(&dream_key).as_deref(), Some("Dream"));
+    assert!(!users.get("notch").unwrap().has_uuid());
 
     // ───── 2ᵉ vague : Dream perd son UUID → doit créer une fenêtre ────
     let batch2 = vec![UsernameResult {
         username: "dream".into(),
         uuid: None,
-        last_seen: Utc::now().to_rfc3339(),
+        last_seen: Utc::now(),
+        canonical: None,
     }];
-    update_batch_status(&users, &batch2, &drop_windows)?;
+    let events = update_batch_status(&users, &batch2, &drop_windows);
     tokio::task::yield_now().await;           // ou sleep 50 ms
 
-
     // Une entrée "dream" doit exister dans `drop_windows`
     let window = drop_windows.get("dream").expect("drop window missing");
     println!("Dream drop window: {:?}", *window);
+    drop(window);
+    assert!(matches!(events.as_slice(), [Event::DropWindow { username, .. }] if username == "Dream"));
+
+    // ───── 3ᵉ vague : Dream est repris → prise signalée ─────────────
+    let batch3 = vec![UsernameResult {
+        username: "dream".into(),
+        uuid: Some(UUID_0.into()),
+        last_seen: Utc::now(),
+        canonical: Some("DREAM".into()),
+    }];
+    let events = update_batch_status(&users, &batch3, &drop_windows);
+    assert!(matches!(events.as_slice(), [Event::Claimed { username, .. }] if username == "DREAM"));
+    assert!(drop_windows.get("dream").is_none());
+
+
     tokio::time::sleep(std::time::Duration::from_millis(5000)).await; // pour laisser le temps à la tâche asynchrone de s'exécuter
 
     Ok(())
@@ -0,0 +1,416 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use super::clock::CLOCK;
+use super::failures::{Failure, FailureStats, Failures};
+
+pub const STATS_PATH: &str = "stats.json";
+
+/// Résultat d'une requête à l'API Mojang.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    RateLimited,
+    Forbidden,
+    BadRequest,
+    /// Autre statut, réponse vide ou erreur réseau.
+    Error,
+    Timeout,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 6] =
+        [Outcome::Ok, Outcome::RateLimited, Outcome::Forbidden, Outcome::BadRequest, Outcome::Error, Outcome::Timeout];
+
+    pub fn from_status(status: usize) -> Self {
+        match status {
+            200 => Outcome::Ok,
+            400 => Outcome::BadRequest,
+            403 => Outcome::Forbidden,
+            429 => Outcome::RateLimited,
+            _ => Outcome::Error,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::RateLimited => "rate_limited",
+            Outcome::Forbidden => "forbidden",
+            Outcome::BadRequest => "bad_request",
+            Outcome::Error => "error",
+            Outcome::Timeout => "timeout",
+        }
+    }
+
+    fn counter(self) -> usize {
+        self as usize
+    }
+}
+
+// index des compteurs : les 6 résultats de requête, puis ceux-ci
+const BATCHES_OK: usize = 6;
+const BATCHES_FAILED: usize = 7;
+const NAMES_CHECKED: usize = 8;
+const COUNTERS: usize = 9;
+
+/// Compteurs sur une période.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Counts {
+    pub ok: u64,
+    pub rate_limited: u64,
+    pub forbidden: u64,
+    pub bad_request: u64,
+    pub errors: u64,
+    pub timeouts: u64,
+    pub batches_ok: u64,
+    pub batches_failed: u64,
+    /// Pseudos dont l'état a été obtenu (résultats de batchs acceptés).
+    pub names_checked: u64,
+}
+
+impl Counts {
+    fn from_array(c: [u64; COUNTERS]) -> Self {
+        Self {
+            ok: c[Outcome::Ok.counter()],
+            rate_limited: c[Outcome::RateLimited.counter()],
+            forbidden: c[Outcome::Forbidden.counter()],
+            bad_request: c[Outcome::BadRequest.counter()],
+            errors: c[Outcome::Error.counter()],
+            timeouts: c[Outcome::Timeout.counter()],
+            batches_ok: c[BATCHES_OK],
+            batches_failed: c[BATCHES_FAILED],
+            names_checked: c[NAMES_CHECKED],
+        }
+    }
+
+    fn to_array(self) -> [u64; COUNTERS] {
+        let mut c = [0; COUNTERS];
+        for outcome in Outcome::ALL {
+            c[outcome.counter()] = self.get(outcome);
+        }
+        c[BATCHES_OK] = self.batches_ok;
+        c[BATCHES_FAILED] = self.batches_failed;
+        c[NAMES_CHECKED] = self.names_checked;
+        c
+    }
+
+    /// Compteurs accumulés depuis `earlier` (relevé antérieur des mêmes totaux).
+    pub fn delta(&self, earlier: &Counts) -> Counts {
+        let (now, before) = (self.to_array(), earlier.to_array());
+        Self::from_array(std::array::from_fn(|i| now[i].saturating_sub(before[i])))
+    }
+
+    pub fn add(&mut self, other: &Counts) {
+        let (a, b) = (self.to_array(), other.to_array());
+        *self = Self::from_array(std::array::from_fn(|i| a[i] + b[i]));
+    }
+
+    pub fn get(&self, outcome: Outcome) -> u64 {
+        match outcome {
+            Outcome::Ok => self.ok,
+            Outcome::RateLimited => self.rate_limited,
+            Outcome::Forbidden => self.forbidden,
+            Outcome::BadRequest => self.bad_request,
+            Outcome::Error => self.errors,
+            Outcome::Timeout => self.timeouts,
+        }
+    }
+
+    /// Requêtes envoyées, tous résultats confondus.
+    pub fn requests(&self) -> u64 {
+        Outcome::ALL.iter().map(|o| self.get(*o)).sum()
+    }
+
+    pub fn batches(&self) -> u64 {
+        self.batches_ok + self.batches_failed
+    }
+}
+
+/// Statistiques d'une fenêtre glissante ; les débits sont rapportés à la
+/// durée réellement couverte (`secs`, plus courte juste après le démarrage).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowStats {
+    pub secs: u64,
+    #[serde(flatten)]
+    pub counts: Counts,
+    pub requests_per_sec: f64,
+    pub batches_per_sec: f64,
+    pub names_per_sec: f64,
+}
+
+impl WindowStats {
+    pub fn new(counts: Counts, secs: u64) -> Self {
+        let rate = |n: u64| if secs > 0 { n as f64 / secs as f64 } else { 0.0 };
+        Self {
+            secs,
+            counts,
+            requests_per_sec: rate(counts.requests()),
+            batches_per_sec: rate(counts.batches()),
+            names_per_sec: rate(counts.names_checked),
+        }
+    }
+}
+
+/// Photo des statistiques, partagée par le journal, les notifications, les
+/// métriques et la commande `stats`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsSnapshot {
+    pub at: DateTime<Utc>,
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    pub lifetime: Counts,
+    pub last_1m: WindowStats,
+    pub last_15m: WindowStats,
+    pub last_1h: WindowStats,
+    /// Échecs par classe depuis le démarrage, avec leurs derniers échantillons.
+    pub failures: Vec<FailureStats>,
+    /// Décalage de l'horloge locale sur celle du serveur, en millisecondes
+    /// (positif si elle retarde) ; absent avant la première réponse.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock_skew_ms: Option<i64>,
+}
+
+impl StatsSnapshot {
+    /// Relit la photo écrite par le scanner en cours.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        serde_json::from_str(&raw).map_err(std::io::Error::other)
+    }
+
+    /// Sans les échantillons d'échecs, pour les notifications.
+    pub fn without_samples(mut self) -> Self {
+        self.failures.iter_mut().for_each(|f| f.samples.clear());
+        self
+    }
+
+    /// Écrit la photo via un fichier temporaire renommé.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self).map_err(std::io::Error::other)?)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+/// Durée d'une case de l'anneau.
+const SLOT_SECS: i64 = 5;
+/// Une heure de cases, plus la case en cours.
+const SLOTS: usize = (3_600 / SLOT_SECS) as usize + 1;
+/// Case en cours de recyclage : son relevé n'est pas encore écrit.
+const RECYCLING: i64 = i64::MIN + 1;
+
+struct Slot {
+    /// Numéro de la période de `SLOT_SECS` secondes que contient la case.
+    epoch: AtomicI64,
+    /// Totaux depuis le démarrage relevés au début de cette période.
+    base: [AtomicU64; COUNTERS],
+}
+
+/// Compteurs sans verrou : totaux depuis le démarrage et anneau de cases de
+/// 5 s pour les fenêtres glissantes (1 min, 15 min, 1 h).
+///
+/// Une case ne compte rien elle-même : le premier incrément d'une nouvelle
+/// période y relève les totaux, et une fenêtre vaut les totaux actuels moins
+/// le relevé de sa première période active. Aucun incrément n'est perdu ; un
+/// incrément concurrent du relevé peut seulement compter pour la période
+/// précédente. Seuls les échantillons d'échecs prennent un verrou, sur le
+/// chemin d'échec.
+pub struct Stats {
+    started_at: DateTime<Utc>,
+    lifetime: [AtomicU64; COUNTERS],
+    slots: Vec<Slot>,
+    failures: Failures,
+}
+
+pub static STATS: Lazy<Stats> = Lazy::new(|| Stats::new(Utc::now()));
+
+impl Stats {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        let stats = Self {
+            started_at,
+            lifetime: std::array::from_fn(|_| AtomicU64::new(0)),
+            slots: (0..SLOTS)
+                .map(|_| Slot { epoch: AtomicI64::new(i64::MIN), base: std::array::from_fn(|_| AtomicU64::new(0)) })
+                .collect(),
+            failures: Failures::default(),
+        };
+        // relevé nul au démarrage : une fenêtre qui le couvre vaut les totaux
+        let epoch = started_at.timestamp().div_euclid(SLOT_SECS);
+        stats.slots[epoch.rem_euclid(SLOTS as i64) as usize].epoch.store(epoch, Ordering::Relaxed);
+        stats
+    }
+
+    /// Échec d'une requête, compté par classe en plus de son résultat.
+    pub fn record_failure(&self, failure: &Failure) {
+        self.failures.record(failure, Utc::now());
+    }
+
+    pub fn record_request(&self, outcome: Outcome) {
+        self.add(Utc::now(), outcome.counter(), 1);
+    }
+
+    /// Batch terminé (accepté ou abandonné) et pseudos dont il a obtenu l'état.
+    pub fn record_batch(&self, success: bool, names_checked: usize) {
+        let now = Utc::now();
+        self.add(now, if success { BATCHES_OK } else { BATCHES_FAILED }, 1);
+        self.add(now, NAMES_CHECKED, names_checked as u64);
+    }
+
+    fn add(&self, now: DateTime<Utc>, counter: usize, n: u64) {
+        if n == 0 {
+            return;
+        }
+        let epoch = now.timestamp().div_euclid(SLOT_SECS);
+        let slot = &self.slots[epoch.rem_euclid(SLOTS as i64) as usize];
+        let current = slot.epoch.load(Ordering::Acquire);
+        // relevé avant l'incrément : il appartient à la nouvelle période
+        if current != RECYCLING
+            && current < epoch
+            && slot.epoch.compare_exchange(current, RECYCLING, Ordering::AcqRel, Ordering::Acquire).is_ok()
+        {
+            for (base, total) in slot.base.iter().zip(&self.lifetime) {
+                base.store(total.load(Ordering::Relaxed), Ordering::Relaxed);
+            }
+            slot.epoch.store(epoch, Ordering::Release);
+        }
+        self.lifetime[counter].fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Relevé de la première période active de `from..=to`, `None` sans activité.
+    fn base_since(&self, from: i64, to: i64) -> Option<[u64; COUNTERS]> {
+        (from..=to).find_map(|epoch| {
+            let slot = &self.slots[epoch.rem_euclid(SLOTS as i64) as usize];
+            (slot.epoch.load(Ordering::Acquire) == epoch)
+                .then(|| std::array::from_fn(|i| slot.base[i].load(Ordering::Relaxed)))
+        })
+    }
+
+    fn totals(&self) -> [u64; COUNTERS] {
+        std::array::from_fn(|i| self.lifetime[i].load(Ordering::Relaxed))
+    }
+
+    /// Compteurs des `count` dernières cases de 5 s, de la plus ancienne à la
+    /// plus récente (case en cours comprise).
+    pub fn recent_slots(&self, count: usize) -> Vec<Counts> {
+        let epoch = Utc::now().timestamp().div_euclid(SLOT_SECS);
+        let count = count.min(SLOTS) as i64;
+        let totals = self.totals();
+        let mut slots = Vec::with_capacity(count as usize);
+        // de la plus récente à la plus ancienne, chaque case finit au relevé suivant
+        let mut next = totals;
+        for e in ((epoch - count + 1)..=epoch).rev() {
+            match self.base_since(e, e) {
+                Some(base) => {
+                    slots.push(Counts::from_array(std::array::from_fn(|i| next[i].saturating_sub(base[i]))));
+                    next = base;
+                }
+                None => slots.push(Counts::default()),
+            }
+        }
+        slots.reverse();
+        slots
+    }
+
+    /// Somme des `secs` dernières secondes (case en cours comprise).
+    fn window(&self, now: DateTime<Utc>, secs: i64) -> WindowStats {
+        let epoch = now.timestamp().div_euclid(SLOT_SECS);
+        let slots = (secs / SLOT_SECS).max(1);
+        let sums = match self.base_since(epoch - slots + 1, epoch) {
+            Some(base) => {
+                let totals = self.totals();
+                std::array::from_fn(|i| totals[i].saturating_sub(base[i]))
+            }
+            None => [0; COUNTERS],
+        };
+        // cases complètes plus la partie écoulée de la case en cours, au plus l'uptime
+        let covered = (slots - 1) * SLOT_SECS + now.timestamp().rem_euclid(SLOT_SECS) + 1;
+        let uptime = (now - self.started_at).num_seconds().max(1);
+        WindowStats::new(Counts::from_array(sums), covered.min(uptime) as u64)
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.snapshot_at(Utc::now())
+    }
+
+    fn snapshot_at(&self, now: DateTime<Utc>) -> StatsSnapshot {
+        StatsSnapshot {
+            at: now,
+            started_at: self.started_at,
+            uptime_secs: (now - self.started_at).num_seconds().max(0),
+            lifetime: Counts::from_array(self.totals()),
+            last_1m: self.window(now, 60),
+            last_15m: self.window(now, 900),
+            last_1h: self.window(now, 3_600),
+            failures: self.failures.snapshot(),
+            clock_skew_ms: CLOCK.skew_ms(),
+        }
+    }
+}
+
+#[test]
+fn test_rolling_windows_and_totals() {
+    let start = DateTime::parse_from_rfc3339("2025-07-14T19:00:00Z").unwrap().with_timezone(&Utc);
+    let at = |secs: i64| start + chrono::Duration::seconds(secs);
+    let stats = Stats::new(start);
+
+    // 2 h d'activité : 1 requête et 1 batch de 10 pseudos par seconde
+    for s in 0..7_200 {
+        stats.add(at(s), Outcome::Ok.counter(), 1);
+        stats.add(at(s), BATCHES_OK, 1);
+        stats.add(at(s), NAMES_CHECKED, 10);
+    }
+    stats.add(at(7_199), Outcome::RateLimited.counter(), 3);
+
+    let snapshot = stats.snapshot_at(at(7_199));
+    assert_eq!(snapshot.uptime_secs, 7_199);
+    assert_eq!(snapshot.lifetime.ok, 7_200);
+    assert_eq!(snapshot.lifetime.requests(), 7_203);
+    assert_eq!(snapshot.last_1m.secs, 60);
+    assert_eq!(snapshot.last_1m.counts.ok, 60);
+    assert_eq!(snapshot.last_1m.counts.rate_limited, 3);
+    assert_eq!(snapshot.last_1m.batches_per_sec, 1.0);
+    assert_eq!(snapshot.last_1m.names_per_sec, 10.0);
+    assert_eq!(snapshot.last_1m.requests_per_sec, 63.0 / 60.0);
+    assert_eq!(snapshot.last_15m.counts.batches_ok, 900);
+    assert_eq!(snapshot.last_1h.counts.batches_ok, 3_600);
+
+    // 10 min sans activité : la minute est vide, l'heure a perdu 10 min
+    let idle = stats.snapshot_at(at(7_799));
+    assert_eq!(idle.last_1m.counts, Counts::default());
+    assert_eq!(idle.last_1h.counts.batches_ok, 3_000);
+
+    // juste après le démarrage, le débit est rapporté à l'uptime
+    let fresh = Stats::new(start);
+    fresh.add(at(1), BATCHES_OK, 4);
+    assert_eq!(fresh.snapshot_at(at(2)).last_1m.secs, 2);
+    assert_eq!(fresh.snapshot_at(at(2)).last_1m.batches_per_sec, 2.0);
+}
+
+#[test]
+fn test_slot_recycle_keeps_concurrent_increments() {
+    let start = DateTime::parse_from_rfc3339("2025-07-14T19:00:00Z").unwrap().with_timezone(&Utc);
+    let stats = Stats::new(start);
+
+    // 8 threads qui changent de case en même temps, 10 min durant
+    std::thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for s in 0..600 {
+                    for _ in 0..20 {
+                        stats.add(start + chrono::Duration::seconds(s), BATCHES_OK, 1);
+                    }
+                }
+            });
+        }
+    });
+    let snapshot = stats.snapshot_at(start + chrono::Duration::seconds(599));
+    assert_eq!(snapshot.lifetime.batches_ok, 96_000);
+    assert_eq!(snapshot.last_1h.counts.batches_ok, 96_000);
+}
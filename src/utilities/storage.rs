@@ -0,0 +1,36 @@
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const DB_PATH: &str = "claimer.db";
+
+/// Connexion SQLite partagée ; les requêtes sont courtes, un mutex suffit.
+pub type Db = Arc<Mutex<Connection>>;
+
+pub fn open_db(path: impl AsRef<Path>) -> rusqlite::Result<Db> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    Ok(Arc::new(Mutex::new(conn)))
+}
+
+/// Base en mémoire, pour les tests.
+pub fn open_memory_db() -> rusqlite::Result<Db> {
+    Ok(Arc::new(Mutex::new(Connection::open_in_memory()?)))
+}
+
+/// Ajoute une colonne à une table existante si elle manque (bases créées
+/// par une version antérieure) ; `definition` suit le nom de la colonne.
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        rusqlite::params![table, column],
+        |row| row.get::<_, i64>(0).map(|n| n > 0),
+    )?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"), [])?;
+    }
+    Ok(())
+}
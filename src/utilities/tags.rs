@@ -0,0 +1,212 @@
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tracing::{error, info, warn};
+
+use super::notifiers::{Event, EventKind};
+use super::sql_management::{insert_name, UsernameMap};
+use super::username::Username;
+
+/// Nombre maximal de tags distincts (un bit chacun dans [`TagSet`]).
+pub const MAX_TAGS: usize = 32;
+
+/// Ensemble de tags d'un pseudo, un bit par tag du [`TagRegistry`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TagSet(u32);
+
+impl TagSet {
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: TagSet) -> TagSet {
+        TagSet(self.0 | other.0)
+    }
+
+    fn bits(self) -> impl Iterator<Item = usize> {
+        (0..MAX_TAGS).filter(move |i| self.0 & (1 << i) != 0)
+    }
+}
+
+/// Noms des tags, dans l'ordre de leurs bits.
+#[derive(Default)]
+pub struct TagRegistry {
+    names: RwLock<Vec<String>>,
+}
+
+pub static TAGS: Lazy<TagRegistry> = Lazy::new(TagRegistry::default);
+
+impl TagRegistry {
+    /// Ensemble contenant les tags donnés, enregistrés au besoin ; au-delà de
+    /// [`MAX_TAGS`] tags distincts, les nouveaux sont ignorés.
+    pub fn set_of<S: AsRef<str>>(&self, tags: &[S]) -> TagSet {
+        let mut set = TagSet::default();
+        for tag in tags {
+            let tag = tag.as_ref().trim();
+            if tag.is_empty() {
+                continue;
+            }
+            if let Some(i) = self.names.read().iter().position(|name| name == tag) {
+                set.0 |= 1 << i;
+                continue;
+            }
+            let mut names = self.names.write();
+            match names.iter().position(|name| name == tag) {
+                Some(i) => set.0 |= 1 << i,
+                None if names.len() < MAX_TAGS => {
+                    set.0 |= 1 << names.len();
+                    names.push(tag.to_string());
+                }
+                None => warn!(tag, max = MAX_TAGS, "trop de tags distincts, tag ignoré"),
+            }
+        }
+        set
+    }
+
+    pub fn names(&self, set: TagSet) -> Vec<String> {
+        let names = self.names.read();
+        set.bits().filter_map(|i| names.get(i).cloned()).collect()
+    }
+}
+
+/// Liste de pseudos à surveiller ; ses pseudos portent le nom du fichier
+/// (`names/3c.txt` → `3c`) en plus des `tags` donnés.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameList {
+    pub path: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl NameList {
+    fn all_tags(&self) -> Vec<String> {
+        let mut tags = self.tags.clone();
+        if let Some(stem) = Path::new(&self.path).file_stem().and_then(|s| s.to_str()) {
+            tags.insert(0, stem.to_string());
+        }
+        tags
+    }
+}
+
+/// Règle de routage par tag : les événements d'un pseudo portant l'un des
+/// `tags` partent aussi vers `sinks`, avec `mention` (ex. `<@&1234>` pour un
+/// rôle Discord) disponible dans le contexte des templates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRule {
+    pub tags: Vec<String>,
+    /// Types d'événements concernés, tous si vide.
+    #[serde(default)]
+    pub kinds: Vec<EventKind>,
+    #[serde(default)]
+    pub sinks: Vec<String>,
+    #[serde(default)]
+    pub mention: Option<String>,
+    /// Les événements concernés partent seulement vers les sinks des règles
+    /// de tags, sans les routes de leur type.
+    #[serde(default)]
+    pub exclusive: bool,
+}
+
+impl TagRule {
+    pub fn matches(&self, event: &Event) -> bool {
+        let kind_ok = self.kinds.is_empty()
+            || self.kinds.contains(&event.kind())
+            || matches!(event, Event::Digest { events } if events.iter().any(|e| self.kinds.contains(&e.kind())));
+        kind_ok && event.tags().iter().any(|tag| self.tags.iter().any(|t| t == tag))
+    }
+}
+
+/// Mentions des règles qui s'appliquent à l'événement pour `sink`.
+pub fn mentions_for<'a>(rules: &'a [TagRule], event: &Event, sink: &str) -> Vec<&'a str> {
+    let mut mentions: Vec<&str> = Vec::new();
+    for rule in rules {
+        if let Some(mention) = &rule.mention {
+            if rule.sinks.iter().any(|s| s == sink) && rule.matches(event) && !mentions.contains(&mention.as_str()) {
+                mentions.push(mention);
+            }
+        }
+    }
+    mentions
+}
+
+/// Charge toutes les listes dans une même map (la première casse vue
+/// l'emporte, un pseudo présent dans plusieurs listes cumule leurs tags),
+/// puis applique les tags explicites `name_tags` (pseudo → tags), numérotés
+/// dans `registry` ([`TAGS`] hors tests).
+pub fn load_name_lists(
+    registry: &TagRegistry,
+    lists: &[NameList],
+    name_tags: &HashMap<String, Vec<String>>,
+) -> std::io::Result<UsernameMap> {
+    let map = UsernameMap::with_capacity_and_hasher_and_shard_amount(70_000, Default::default(), 1_024);
+    for list in lists {
+        let path = Path::new(&list.path);
+        if !path.exists() {
+            error!(path = %list.path, "liste de pseudos introuvable");
+            continue;
+        }
+        let tags = registry.set_of(&list.all_tags());
+        let before = map.len();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            insert_name(&map, &line);
+            if let Some(mut state) = map.get_mut(Username::key_of(&line).as_str()) {
+                state.add_tags(tags);
+            }
+        }
+        info!(path = %list.path, added = map.len() - before, tags = ?list.all_tags(), "liste de pseudos chargée");
+    }
+    for (name, tags) in name_tags {
+        match map.get_mut(Username::key_of(name).as_str()) {
+            Some(mut state) => state.add_tags(registry.set_of(tags)),
+            None => warn!(username = %name, "tags pour un pseudo non suivi"),
+        }
+    }
+    Ok(map)
+}
+
+#[test]
+fn test_lists_tags_and_rules() -> std::io::Result<()> {
+    use super::sql_management::DropWindow;
+
+    let dir = std::env::temp_dir().join(format!("tags_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("3c.txt"), "abc\nXyz\n")?;
+    std::fs::write(dir.join("words.txt"), "apple\nxyz\n")?;
+    let lists = vec![
+        NameList { path: dir.join("3c.txt").display().to_string(), tags: vec![] },
+        NameList { path: dir.join("words.txt").display().to_string(), tags: vec!["dico".into()] },
+    ];
+    let registry = TagRegistry::default();
+    let map = load_name_lists(&registry, &lists, &HashMap::from([("ABC".to_string(), vec!["vip".to_string()])]))?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(registry.names(map.get("abc").unwrap().tags()), vec!["3c", "vip"]);
+    assert_eq!(registry.names(map.get("xyz").unwrap().tags()), vec!["3c", "words", "dico"]);
+
+    let now = chrono::Utc::now();
+    let drop = Event::DropWindow {
+        username: "Xyz".into(),
+        window: DropWindow { begin: now, end: now },
+        tags: registry.names(map.get("xyz").unwrap().tags()),
+    };
+    let rules = vec![
+        TagRule { tags: vec!["dico".into()], kinds: vec![], sinks: vec!["words".into()], mention: Some("<@&1>".into()), exclusive: false },
+        TagRule { tags: vec!["vip".into()], kinds: vec![], sinks: vec!["words".into()], mention: Some("<@&2>".into()), exclusive: false },
+        TagRule {
+            tags: vec!["3c".into()],
+            kinds: vec![EventKind::Claimed],
+            sinks: vec!["words".into()],
+            mention: Some("<@&3>".into()),
+            exclusive: false,
+        },
+    ];
+    assert_eq!(mentions_for(&rules, &drop, "words"), vec!["<@&1>"]);
+    assert!(mentions_for(&rules, &drop, "other").is_empty());
+    Ok(())
+}
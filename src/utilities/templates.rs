@@ -0,0 +1,564 @@
+//! Templates des messages de notification (minijinja).
+//!
+//! Pour un événement et un sink, le premier template trouvé parmi
+//! `<event>.<nom du sink>.j2`, `<event>.<type du sink>.j2` puis `<event>.j2`
+//! est utilisé ; chaque nom est cherché dans le dossier `templates_dir` de la
+//! configuration puis dans les templates par défaut intégrés au binaire
+//! (dossier `templates/` du dépôt). Un template de fichier qui échoue au rendu
+//! est signalé et le suivant est essayé.
+//!
+//! Un template définit des blocs, tous optionnels :
+//! - `title` : titre (sujet d'email, titre d'embed Discord…)
+//! - `body` : corps du message
+//! - `content` : texte Discord affiché hors de l'embed
+//! - `footer` : pied d'embed Discord
+//!
+//! Contexte disponible :
+//! - `event` : `checkpoint`, `drop_window`, `reminder`, `window_ended`, `claimed`,
+//!   `digest`, `escalation`, `watchdog`, `report`
+//! - `sink`, `sink_type` : nom et type du sink destinataire
+//! - `now` : instant du rendu (RFC 3339)
+//! - `username` : pseudo concerné (absent pour le checkpoint)
+//! - `tags` : tags du pseudo (pour un résumé, ceux de tous ses événements)
+//! - `mentions` : mentions des règles `tag_routes` qui visent ce sink (ex. `<@&1234>`)
+//! - `window` : `begin`, `end` (RFC 3339), `begin_unix`, `end_unix` (à la µs),
+//!   `duration` (`02m 03s 004ms`), `duration_ms`, `zones` (liste de `{name, begin, end}`
+//!   dans les fuseaux d'affichage), `lines` (une ligne par fuseau puis Unix),
+//!   `discord_begin`, `discord_end` (`<t:…:F>`), `discord_begin_relative` (`<t:…:R>`)
+//! - `minutes_before`, `lead` (`1h30`) : pour les rappels, et pour les escalades
+//!   (minutes restantes avant l'ouverture, `0` si la fenêtre est déjà ouverte)
+//! - `repeat` : numéro de l'alerte d'escalade (1 pour la première)
+//! - `watchdog` : pour une alerte du watchdog, `problem` (`no_success`,
+//!   `low_success_rate`, `checkpoint_stalled`, `clock_skew`), `label`, `recovered`, `since`
+//!   (RFC 3339), `since_local` (premier fuseau d'affichage), `discord_since`
+//!   (`<t:…:F>`), `detail` et `down` (durée du problème, `1h30`)
+//! - `report` : pour un résumé quotidien ou hebdomadaire, `period` (`daily`,
+//!   `weekly`), `label`, `from`, `to` (dates locales incluses), `totals` et `days`
+//!   (une entrée par journée avec des données, avec `day`) : compteurs bruts (`ok`,
+//!   `rate_limited`, `forbidden`, `bad_request`, `errors`, `timeouts`, `batches_ok`,
+//!   `batches_failed`, `names_checked`, `drops_detected`, `claims_observed`,
+//!   `windows_opened`, `windows_closed`, `names_total`, `names_covered`), `requests`,
+//!   `batches`, `success_pct` et `coverage_pct` (à 0,1 près)
+//! - `items` : pour un résumé (`digest`), la liste des événements regroupés,
+//!   chacun avec `event`, `username`, `window`, `minutes_before` et `lead`
+//! - `stats` : pour le checkpoint, sur la dernière minute (`interval_secs`) : requêtes
+//!   `ok`, `rate_limited`, `forbidden`, `other` (400, erreurs réseau, délais dépassés),
+//!   `total`, leurs pourcentages `ok_pct`, `rate_limited_pct`, `forbidden_pct`,
+//!   `other_pct` ; `batches`, `errors` (batchs abandonnés) et `errors_pct`,
+//!   `names_checked`, débits `requests_per_sec`, `batches_per_sec`, `names_per_sec` ;
+//!   `last_15m`, `last_1h` (mêmes compteurs bruts et débits) et `lifetime` (totaux
+//!   depuis le démarrage : `ok`, `rate_limited`, `forbidden`, `bad_request`, `errors`,
+//!   `timeouts`, `batches_ok`, `batches_failed`, `names_checked`) ; `quarantine_total`,
+//!   `quarantined` (pseudos ajoutés depuis le dernier checkpoint), `uptime`
+//!   (`1D 02H 03m 04s`), `uptime_secs`
+//!
+//! Filtre supplémentaire : `minutes` (`90|minutes` → `1h30`).
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use minijinja::{Environment, ErrorKind};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use super::clock::CLOCK;
+use super::notifiers::{CheckpointStats, Event};
+use super::reports::{Period, Rollup, Summary};
+use super::tags::{mentions_for, TagRule};
+use super::sql_management::DropWindow;
+use super::stats::{Counts, WindowStats};
+use super::watchdog::Problem;
+use super::time_display::{format_in, unix_micros, window_lines};
+
+/// Templates par défaut, embarqués dans le binaire.
+const BUILTIN: &[(&str, &str)] = &[
+    ("checkpoint.j2", include_str!("../../templates/checkpoint.j2")),
+    ("checkpoint.discord.j2", include_str!("../../templates/checkpoint.discord.j2")),
+    ("drop_window.j2", include_str!("../../templates/drop_window.j2")),
+    ("drop_window.discord.j2", include_str!("../../templates/drop_window.discord.j2")),
+    ("reminder.j2", include_str!("../../templates/reminder.j2")),
+    ("reminder.discord.j2", include_str!("../../templates/reminder.discord.j2")),
+    ("window_ended.j2", include_str!("../../templates/window_ended.j2")),
+    ("claimed.j2", include_str!("../../templates/claimed.j2")),
+    ("digest.j2", include_str!("../../templates/digest.j2")),
+    ("digest.discord.j2", include_str!("../../templates/digest.discord.j2")),
+    ("escalation.j2", include_str!("../../templates/escalation.j2")),
+    ("escalation.discord.j2", include_str!("../../templates/escalation.discord.j2")),
+    ("watchdog.j2", include_str!("../../templates/watchdog.j2")),
+    ("watchdog.discord.j2", include_str!("../../templates/watchdog.discord.j2")),
+    ("report.j2", include_str!("../../templates/report.j2")),
+    ("report.discord.j2", include_str!("../../templates/report.discord.j2")),
+];
+
+/// Message rendu, prêt à être mis en forme par un sink.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Message {
+    pub title: String,
+    pub body: String,
+    pub content: String,
+    pub footer: String,
+}
+
+#[derive(Serialize)]
+struct Context<'a> {
+    event: &'static str,
+    sink: &'a str,
+    sink_type: &'a str,
+    now: DateTime<Utc>,
+    username: Option<&'a str>,
+    tags: Vec<&'a str>,
+    mentions: Vec<&'a str>,
+    window: Option<WindowContext>,
+    minutes_before: Option<i64>,
+    lead: Option<String>,
+    repeat: Option<u32>,
+    stats: Option<StatsContext<'a>>,
+    watchdog: Option<WatchdogContext<'a>>,
+    report: Option<ReportContext<'a>>,
+    items: Vec<Context<'a>>,
+}
+
+#[derive(Serialize)]
+struct WatchdogContext<'a> {
+    problem: Problem,
+    label: &'static str,
+    recovered: bool,
+    since: DateTime<Utc>,
+    /// `since` dans le premier fuseau d'affichage.
+    since_local: String,
+    /// `<t:…:F>`
+    discord_since: String,
+    detail: &'a str,
+    /// Durée du problème jusqu'au rendu (`1h30`).
+    down: String,
+}
+
+#[derive(Serialize)]
+struct ReportContext<'a> {
+    period: Period,
+    label: &'static str,
+    from: NaiveDate,
+    to: NaiveDate,
+    totals: RollupContext<'a>,
+    days: Vec<RollupContext<'a>>,
+}
+
+#[derive(Serialize)]
+struct RollupContext<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    day: Option<NaiveDate>,
+    #[serde(flatten)]
+    rollup: &'a Rollup,
+    requests: u64,
+    batches: u64,
+    success_pct: f64,
+    coverage_pct: f64,
+}
+
+impl<'a> RollupContext<'a> {
+    fn new(day: Option<NaiveDate>, rollup: &'a Rollup) -> Self {
+        let round = |pct: f64| (pct * 10.0).round() / 10.0;
+        Self {
+            day,
+            rollup,
+            requests: rollup.counts.requests(),
+            batches: rollup.counts.batches(),
+            success_pct: round(rollup.success_pct()),
+            coverage_pct: round(rollup.coverage_pct()),
+        }
+    }
+}
+
+impl<'a> ReportContext<'a> {
+    fn new(summary: &'a Summary) -> Self {
+        Self {
+            period: summary.period,
+            label: summary.period.label(),
+            from: summary.from,
+            to: summary.to,
+            totals: RollupContext::new(None, &summary.totals),
+            days: summary.days.iter().map(|day| RollupContext::new(Some(day.day), &day.rollup)).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ZoneContext {
+    name: &'static str,
+    begin: String,
+    end: String,
+}
+
+#[derive(Serialize)]
+struct WindowContext {
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+    begin_unix: String,
+    end_unix: String,
+    duration: String,
+    duration_ms: i64,
+    zones: Vec<ZoneContext>,
+    lines: Vec<String>,
+    discord_begin: String,
+    discord_end: String,
+    discord_begin_relative: String,
+}
+
+impl WindowContext {
+    fn new(window: &DropWindow, zones: &[Tz]) -> Self {
+        let duration_ms = (window.end - window.begin).num_milliseconds();
+        Self {
+            begin: window.begin,
+            end: window.end,
+            begin_unix: unix_micros(window.begin),
+            end_unix: unix_micros(window.end),
+            duration: format!(
+                "{:02}m {:02}s {:03}ms",
+                duration_ms / 60_000,
+                (duration_ms % 60_000) / 1_000,
+                duration_ms % 1_000
+            ),
+            duration_ms,
+            zones: zones
+                .iter()
+                .map(|tz| ZoneContext { name: tz.name(), begin: format_in(window.begin, *tz), end: format_in(window.end, *tz) })
+                .collect(),
+            lines: window_lines(window, zones),
+            discord_begin: format!("<t:{}:F>", window.begin.timestamp()),
+            discord_end: format!("<t:{}:F>", window.end.timestamp()),
+            discord_begin_relative: format!("<t:{}:R>", window.begin.timestamp()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StatsContext<'a> {
+    interval_secs: u64,
+    ok: u64,
+    rate_limited: u64,
+    forbidden: u64,
+    other: u64,
+    total: u64,
+    ok_pct: i64,
+    rate_limited_pct: i64,
+    forbidden_pct: i64,
+    other_pct: i64,
+    batches: u64,
+    errors: u64,
+    errors_pct: f64,
+    names_checked: u64,
+    requests_per_sec: f64,
+    batches_per_sec: f64,
+    names_per_sec: f64,
+    last_15m: &'a WindowStats,
+    last_1h: &'a WindowStats,
+    lifetime: &'a Counts,
+    quarantine_total: usize,
+    quarantined: &'a [String],
+    uptime: String,
+    uptime_secs: i64,
+}
+
+impl<'a> StatsContext<'a> {
+    fn new(stats: &'a CheckpointStats) -> Self {
+        let window = &stats.snapshot.last_1m;
+        let counts = &window.counts;
+        let total = counts.requests();
+        let other = total - counts.ok - counts.rate_limited - counts.forbidden;
+        // pourcentages des requêtes par résultat, arrondis à l'unité
+        let pct = |n: u64, of: u64| if of > 0 { (n as f64 / of as f64 * 100.0).round() as i64 } else { 0 };
+        let round = |rate: f64| (rate * 10.0).round() / 10.0;
+        let up = stats.snapshot.uptime_secs.max(0);
+        Self {
+            interval_secs: window.secs,
+            ok: counts.ok,
+            rate_limited: counts.rate_limited,
+            forbidden: counts.forbidden,
+            other,
+            total,
+            ok_pct: pct(counts.ok, total),
+            rate_limited_pct: pct(counts.rate_limited, total),
+            forbidden_pct: pct(counts.forbidden, total),
+            other_pct: pct(other, total),
+            batches: counts.batches(),
+            errors: counts.batches_failed,
+            errors_pct: if counts.batches() > 0 { counts.batches_failed as f64 / counts.batches() as f64 * 100.0 } else { 0.0 },
+            names_checked: counts.names_checked,
+            requests_per_sec: round(window.requests_per_sec),
+            batches_per_sec: round(window.batches_per_sec),
+            names_per_sec: round(window.names_per_sec),
+            last_15m: &stats.snapshot.last_15m,
+            last_1h: &stats.snapshot.last_1h,
+            lifetime: &stats.snapshot.lifetime,
+            quarantine_total: stats.quarantine_total,
+            quarantined: &stats.quarantined,
+            uptime: format_uptime(up),
+            uptime_secs: up,
+        }
+    }
+}
+
+/// `93784` → `1D 02H 03m 04s`.
+pub fn format_uptime(secs: i64) -> String {
+    let up = secs.max(0);
+    format!("{}D {:02}H {:02}m {:02}s", up / 86_400, (up % 86_400) / 3_600, (up % 3_600) / 60, up % 60)
+}
+
+/// `90` → `1h30`, `1440` → `24h`, `5` → `5 min`.
+pub fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{} min", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{:02}", h, m),
+    }
+}
+
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_filter("minutes", format_minutes);
+    env
+}
+
+/// Templates de fichiers (chargés à la demande puis gardés en cache) et
+/// templates par défaut.
+pub struct Templates {
+    files: Option<Environment<'static>>,
+    builtin: Environment<'static>,
+    zones: Vec<Tz>,
+    tag_routes: Vec<TagRule>,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::builtin(vec![chrono_tz::Europe::Paris])
+    }
+}
+
+impl Templates {
+    /// Templates par défaut uniquement.
+    pub fn builtin(zones: Vec<Tz>) -> Self {
+        let mut builtin = environment();
+        for (name, source) in BUILTIN {
+            builtin.add_template(name, source).expect("built-in template must compile");
+        }
+        Self { files: None, builtin, zones, tag_routes: Vec::new() }
+    }
+
+    /// Templates du dossier `dir`, complétés par les templates par défaut.
+    pub fn load(dir: impl AsRef<Path>, zones: Vec<Tz>) -> Self {
+        let dir: PathBuf = dir.as_ref().to_path_buf();
+        let mut templates = Self::builtin(zones);
+        if dir.is_dir() {
+            let mut files = environment();
+            files.set_loader(move |name| match fs::read_to_string(dir.join(name)) {
+                Ok(source) => Ok(Some(source)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(minijinja::Error::new(ErrorKind::InvalidOperation, "template illisible").with_source(e)),
+            });
+            templates.files = Some(files);
+        }
+        templates
+    }
+
+    /// Règles dont les mentions sont ajoutées au contexte (`mentions`).
+    pub fn with_tag_routes(mut self, tag_routes: Vec<TagRule>) -> Self {
+        self.tag_routes = tag_routes;
+        self
+    }
+
+    /// Rend `event` pour le sink `sink` de type `sink_type`.
+    pub fn render(&self, event: &Event, sink: &str, sink_type: &str) -> Message {
+        let kind = event_name(event);
+        let context = self.context(event, sink, sink_type);
+        let candidates = [format!("{kind}.{sink}.j2"), format!("{kind}.{sink_type}.j2"), format!("{kind}.j2")];
+
+        for name in &candidates {
+            if let Some(files) = &self.files {
+                match render_with(files, name, &context) {
+                    Ok(Some(message)) => return message,
+                    Ok(None) => {}
+                    Err(e) => warn!(template = %name, error = %format!("{:#}", e), "template invalide, template suivant utilisé"),
+                }
+            }
+            match render_with(&self.builtin, name, &context) {
+                Ok(Some(message)) => return message,
+                Ok(None) => {}
+                Err(e) => error!(template = %name, error = %format!("{:#}", e), "template intégré invalide"),
+            }
+        }
+        // tous les événements ont un template `<event>.j2` intégré
+        Message { title: kind.to_string(), ..Message::default() }
+    }
+
+    fn context<'a>(&'a self, event: &'a Event, sink: &'a str, sink_type: &'a str) -> Context<'a> {
+        let mut context = Context {
+            event: event_name(event),
+            sink,
+            sink_type,
+            now: Utc::now(),
+            username: event.username(),
+            tags: event.tags(),
+            mentions: mentions_for(&self.tag_routes, event, sink),
+            window: None,
+            minutes_before: None,
+            lead: None,
+            repeat: None,
+            stats: None,
+            watchdog: None,
+            report: None,
+            items: Vec::new(),
+        };
+        match event {
+            Event::Digest { events } => {
+                context.items = events.iter().map(|held| self.context(held, sink, sink_type)).collect();
+            }
+            Event::Checkpoint { stats } => context.stats = Some(StatsContext::new(stats)),
+            Event::Reminder { window, minutes_before, .. } => {
+                context.window = Some(WindowContext::new(window, &self.zones));
+                context.minutes_before = Some(*minutes_before);
+                context.lead = Some(format_minutes(*minutes_before));
+            }
+            Event::Escalation { window, repeat, .. } => {
+                let minutes_before = (window.begin - CLOCK.corrected(context.now)).num_minutes().max(0);
+                context.window = Some(WindowContext::new(window, &self.zones));
+                context.minutes_before = Some(minutes_before);
+                context.lead = Some(format_minutes(minutes_before));
+                context.repeat = Some(*repeat);
+            }
+            Event::DropWindow { window, .. } | Event::WindowEnded { window, .. } | Event::Claimed { window, .. } => {
+                context.window = Some(WindowContext::new(window, &self.zones));
+            }
+            Event::Watchdog { problem, recovered, since, detail } => {
+                context.watchdog = Some(WatchdogContext {
+                    problem: *problem,
+                    label: problem.label(),
+                    recovered: *recovered,
+                    since: *since,
+                    since_local: format_in(*since, self.zones.first().copied().unwrap_or(Tz::UTC)),
+                    discord_since: format!("<t:{}:F>", since.timestamp()),
+                    detail,
+                    down: format_minutes((context.now - *since).num_minutes().max(0)),
+                });
+            }
+            Event::Report { summary } => context.report = Some(ReportContext::new(summary)),
+        }
+        context
+    }
+}
+
+/// Templates vus par un sink : son nom et son type fixent la recherche.
+#[derive(Clone)]
+pub struct SinkTemplates {
+    templates: Arc<Templates>,
+    sink: String,
+    sink_type: &'static str,
+}
+
+impl SinkTemplates {
+    pub fn new(templates: Arc<Templates>, sink: &str, sink_type: &'static str) -> Self {
+        Self { templates, sink: sink.to_string(), sink_type }
+    }
+
+    /// Templates par défaut, sink nommé d'après son type.
+    pub fn builtin(sink_type: &'static str) -> Self {
+        Self::new(Arc::new(Templates::default()), sink_type, sink_type)
+    }
+
+    pub fn render(&self, event: &Event) -> Message {
+        self.templates.render(event, &self.sink, self.sink_type)
+    }
+}
+
+fn event_name(event: &Event) -> &'static str {
+    match event {
+        Event::Checkpoint { .. } => "checkpoint",
+        Event::DropWindow { .. } => "drop_window",
+        Event::Reminder { .. } => "reminder",
+        Event::WindowEnded { .. } => "window_ended",
+        Event::Claimed { .. } => "claimed",
+        Event::Digest { .. } => "digest",
+        Event::Escalation { .. } => "escalation",
+        Event::Watchdog { .. } => "watchdog",
+        Event::Report { .. } => "report",
+    }
+}
+
+/// `Ok(None)` si le template n'existe pas dans cet environnement.
+fn render_with(env: &Environment<'static>, name: &str, context: &Context<'_>) -> Result<Option<Message>, minijinja::Error> {
+    let template = match env.get_template(name) {
+        Ok(template) => template,
+        Err(e) if e.kind() == ErrorKind::TemplateNotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    // un seul rendu : les blocs sont lus dans le même état
+    template.render_captured(context)?.with_state_mut(|state| {
+        let mut block = |block: &str| match state.render_block(block) {
+            Ok(text) => Ok(text.trim().to_string()),
+            Err(e) if e.kind() == ErrorKind::UnknownBlock => Ok(String::new()),
+            Err(e) => Err(e),
+        };
+        Ok(Some(Message { title: block("title")?, body: block("body")?, content: block("content")?, footer: block("footer")? }))
+    })
+}
+
+#[test]
+fn test_templates_lookup_and_context() {
+    let begin = DateTime::parse_from_rfc3339("2025-07-14T19:03:07.25Z").unwrap().with_timezone(&Utc);
+    let window = DropWindow { begin, end: begin + chrono::Duration::milliseconds(90_500) };
+    let drop = Event::DropWindow { username: "Dream".into(), window, tags: vec!["3c".into()] };
+    let last_1m = WindowStats::new(Counts { ok: 90, rate_limited: 10, batches_ok: 95, batches_failed: 5, ..Default::default() }, 60);
+    let checkpoint = Event::Checkpoint {
+        stats: CheckpointStats {
+            snapshot: Box::new(super::stats::StatsSnapshot { uptime_secs: 93_784, last_1m, ..Default::default() }),
+            ..Default::default()
+        },
+    };
+
+    let builtin = Templates::builtin(vec![chrono_tz::Europe::Paris]);
+    let message = builtin.render(&drop, "discord_drops", "discord");
+    assert_eq!(message.title, "Dream");
+    assert_eq!(message.content, "||drop incoming||");
+    assert!(message.body.contains("`Europe/Paris : 2025-07-14 21:03:07.250 CEST → 2025-07-14 21:04:37.750 CEST`"));
+    assert!(message.body.contains("**Durée** : 01m 30s 500ms"));
+    assert!(message.body.contains("<t:1752519787:F>"));
+    let checkpoint_message = builtin.render(&checkpoint, "console", "console");
+    assert!(checkpoint_message.body.contains("| 200 : 90 | 90 %"));
+    assert!(checkpoint_message.body.contains("Uptime : 1D 02H 03m 04s"));
+    assert!(checkpoint_message.body.contains("Batchs : 100 | abandonnés : 5 ❌ 5.0 %"));
+    let day = NaiveDate::from_ymd_opt(2025, 7, 13).unwrap();
+    let rollup = Rollup { counts: Counts { ok: 2, errors: 1, ..Default::default() }, names_total: 3, names_covered: 2, ..Default::default() };
+    let report = Event::Report {
+        summary: Box::new(Summary {
+            period: Period::Weekly,
+            from: day - chrono::Duration::days(6),
+            to: day,
+            totals: rollup,
+            days: vec![super::reports::DailyRollup { day, rollup }],
+        }),
+    };
+    let report_message = builtin.render(&report, "console", "console");
+    assert_eq!(report_message.title, "Résumé hebdomadaire · 2025-07-07 → 2025-07-13");
+    assert!(report_message.body.contains("Requêtes : 3 | 200 : 66.7 % |"));
+    assert!(report_message.body.contains("couverture : 66.7 %"));
+
+    // mention des règles de tags qui visent ce sink
+    let rule = TagRule { tags: vec!["3c".into()], kinds: vec![], sinks: vec!["discord_drops".into()], mention: Some("<@&42>".into()), exclusive: false };
+    let tagged = Templates::builtin(vec![chrono_tz::UTC]).with_tag_routes(vec![rule]);
+    assert_eq!(tagged.render(&drop, "discord_drops", "discord").content, "<@&42> ||drop incoming||");
+    assert_eq!(tagged.render(&drop, "discord_logs", "discord").content, "||drop incoming||");
+
+    // un fichier par nom de sink prime ; un template cassé retombe sur le suivant
+    let dir = std::env::temp_dir().join(format!("templates_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("drop_window.alerts.j2"), "{% block title %}{{ username|upper }} {{ 90|minutes }}{% endblock %}").unwrap();
+    fs::write(dir.join("checkpoint.slack.j2"), "{% block body %}{{ stats.nope() }}{% endblock %}").unwrap();
+    let templates = Templates::load(&dir, vec![chrono_tz::UTC]);
+    assert_eq!(templates.render(&drop, "alerts", "discord").title, "DREAM 1h30");
+    assert_eq!(templates.render(&drop, "other", "discord").title, "Dream");
+    assert!(templates.render(&checkpoint, "team", "slack").body.contains("Req/s : 1.7 | Batchs/s : 1.7 |"));
+    fs::remove_dir_all(&dir).unwrap();
+}
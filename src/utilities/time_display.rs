@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::borrow::Cow;
+
+use super::sql_management::DropWindow;
+
+/// Instant dans un fuseau, ex. `2025-07-14 21:03:07.250 CEST`.
+pub fn format_in(at: DateTime<Utc>, tz: Tz) -> String {
+    at.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S%.3f %Z").to_string()
+}
+
+/// Timestamp Unix à la microseconde, ex. `1752519787.250000`.
+pub fn unix_micros(at: DateTime<Utc>) -> String {
+    let micros = at.timestamp_micros();
+    format!("{}.{:06}", micros.div_euclid(1_000_000), micros.rem_euclid(1_000_000))
+}
+
+/// Une ligne par fuseau (`fuseau : début → fin`) puis une ligne Unix.
+pub fn window_lines(window: &DropWindow, zones: &[Tz]) -> Vec<String> {
+    let mut lines: Vec<String> = zones
+        .iter()
+        .map(|tz| format!("{} : {} → {}", tz.name(), format_in(window.begin, *tz), format_in(window.end, *tz)))
+        .collect();
+    lines.push(format!("Unix : {} → {}", unix_micros(window.begin), unix_micros(window.end)));
+    lines
+}
+
+/// Champ CSV (RFC 4180) : entre guillemets, guillemets doublés, s'il
+/// contient `,`, `"` ou un saut de ligne.
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// En-tête CSV correspondant à [`window_csv_row`].
+pub fn window_csv_header(zones: &[Tz]) -> String {
+    let mut columns = vec!["username".to_string()];
+    for tz in zones {
+        columns.push(csv_field(&format!("begin ({})", tz.name())).into_owned());
+        columns.push(csv_field(&format!("end ({})", tz.name())).into_owned());
+    }
+    columns.push("begin_unix".to_string());
+    columns.push("end_unix".to_string());
+    columns.join(",")
+}
+
+pub fn window_csv_row(username: &str, window: &DropWindow, zones: &[Tz]) -> String {
+    let mut columns = vec![csv_field(username).into_owned()];
+    for tz in zones {
+        columns.push(format_in(window.begin, *tz));
+        columns.push(format_in(window.end, *tz));
+    }
+    columns.push(unix_micros(window.begin));
+    columns.push(unix_micros(window.end));
+    columns.join(",")
+}
+
+#[test]
+fn test_window_lines_all_zones() {
+    let begin = DateTime::parse_from_rfc3339("2025-07-14T19:03:07.25Z").unwrap().with_timezone(&Utc);
+    let window = DropWindow { begin, end: begin + chrono::Duration::seconds(90) };
+    let lines = window_lines(&window, &[chrono_tz::Europe::Paris, chrono_tz::Asia::Tokyo]);
+
+    assert_eq!(lines[0], "Europe/Paris : 2025-07-14 21:03:07.250 CEST → 2025-07-14 21:04:37.250 CEST");
+    assert_eq!(lines[1], "Asia/Tokyo : 2025-07-15 04:03:07.250 JST → 2025-07-15 04:04:37.250 JST");
+    assert_eq!(lines[2], "Unix : 1752519787.250000 → 1752519877.250000");
+
+    let row = window_csv_row("a,\"b\"\nc", &window, &[chrono_tz::UTC]);
+    assert!(row.starts_with("\"a,\"\"b\"\"\nc\",2025-07-14 19:03:07.250 UTC,"));
+    assert_eq!(csv_field("Dream"), "Dream");
+}
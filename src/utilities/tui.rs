@@ -0,0 +1,299 @@
+//! Tableau de bord en terminal (`claimer_rs_full tui`) : débit par résultat,
+//! fraîcheur des vérifications, concurrence, prochaines fenêtres de drop,
+//! santé des proxies et derniers logs.
+//!
+//! Touches : `q` / `Échap` / `Ctrl-C` quitte (et arrête le scanner), `p` met
+//! en pause ou relance, `+` / `-` double ou divise par deux la concurrence.
+
+use chrono::{DateTime, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{BarChart, Block, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+use super::clock::CLOCK;
+use super::control::ScanControl;
+use super::logging::LogBuffer;
+use super::proxy_management::{ProxyHealth, ProxySummary};
+use super::sql_management::UsernameMap;
+use super::stats::{StatsSnapshot, WindowStats, STATS};
+use super::templates::format_uptime;
+use super::window_store::{StoredWindow, WindowStore};
+
+/// Historique affiché : 5 minutes de cases de 5 s.
+const HISTORY_SLOTS: usize = 60;
+const NEXT_WINDOWS: usize = 10;
+
+/// Données lues à chaque rafraîchissement.
+pub struct DashboardSources {
+    pub names: Arc<UsernameMap>,
+    pub windows: Arc<WindowStore>,
+    pub control: Arc<ScanControl>,
+    pub proxies: Arc<ProxyHealth>,
+    pub logs: LogBuffer,
+}
+
+struct DashboardData {
+    now: DateTime<Utc>,
+    paused: bool,
+    concurrency: usize,
+    in_flight: usize,
+    names_tracked: usize,
+    stats: StatsSnapshot,
+    /// Requêtes réussies par case de 5 s.
+    history: Vec<u64>,
+    staleness: Vec<(&'static str, u64)>,
+    windows: Vec<StoredWindow>,
+    proxies: ProxySummary,
+    logs: Vec<String>,
+}
+
+impl DashboardData {
+    fn collect(sources: &DashboardSources) -> Self {
+        let now = CLOCK.corrected(Utc::now());
+        let mut windows = sources.windows.open_windows().unwrap_or_else(|e| {
+            error!(error = %e, "tableau de bord : lecture des fenêtres impossible");
+            Vec::new()
+        });
+        windows.retain(|w| w.window.end > now);
+        windows.sort_by_key(|w| w.window.begin);
+        windows.truncate(NEXT_WINDOWS);
+        Self {
+            now,
+            paused: sources.control.is_paused(),
+            concurrency: sources.control.concurrency(),
+            in_flight: sources.control.in_flight(),
+            names_tracked: sources.control.names_len(),
+            stats: STATS.snapshot(),
+            history: STATS.recent_slots(HISTORY_SLOTS).iter().map(|c| c.ok).collect(),
+            staleness: staleness(&sources.names, now),
+            windows,
+            proxies: sources.proxies.summary(5),
+            logs: sources.logs.last(100),
+        }
+    }
+}
+
+/// Répartition des pseudos par ancienneté de leur dernière vérification.
+fn staleness(names: &UsernameMap, now: DateTime<Utc>) -> Vec<(&'static str, u64)> {
+    const BUCKETS: [(&str, i64); 5] = [("<1m", 60), ("1-5m", 300), ("5-15m", 900), ("15-60m", 3_600), (">1h", i64::MAX)];
+    let mut counts = [0u64; BUCKETS.len() + 1];
+    for entry in names.iter() {
+        match entry.value().last_seen() {
+            Some(seen) => {
+                let age = (now - seen).num_seconds();
+                let i = BUCKETS.iter().position(|(_, max)| age < *max).unwrap_or(BUCKETS.len() - 1);
+                counts[i] += 1;
+            }
+            None => counts[BUCKETS.len()] += 1,
+        }
+    }
+    BUCKETS.iter().map(|(label, _)| *label).chain(["jamais"]).zip(counts).collect()
+}
+
+fn throughput_row(label: &str, window: &WindowStats) -> Row<'static> {
+    let c = &window.counts;
+    Row::new(vec![
+        label.to_string(),
+        format!("{:.1}", window.requests_per_sec),
+        c.ok.to_string(),
+        c.rate_limited.to_string(),
+        c.forbidden.to_string(),
+        (c.bad_request + c.errors + c.timeouts).to_string(),
+        format!("{:.1}", window.batches_per_sec),
+        c.batches_failed.to_string(),
+        format!("{:.1}", window.names_per_sec),
+    ])
+}
+
+fn window_row(stored: &StoredWindow, now: DateTime<Utc>) -> Row<'static> {
+    let window = &stored.window;
+    let (state, style) = if window.begin > now {
+        (format!("dans {}", format_uptime((window.begin - now).num_seconds())), Style::default())
+    } else {
+        (format!("ouverte, encore {}", format_uptime((window.end - now).num_seconds())), Style::default().fg(Color::Red).bold())
+    };
+    Row::new(vec![stored.username.clone(), state, stored.tags.join(", ")]).style(style)
+}
+
+fn draw(frame: &mut Frame, data: &DashboardData) {
+    let [header, top, middle, bottom, logs] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(NEXT_WINDOWS as u16 + 3),
+        Constraint::Min(4),
+    ])
+    .areas(frame.area());
+
+    let state = if data.paused { "EN PAUSE".yellow().bold() } else { "en cours".green().bold() };
+    frame.render_widget(
+        Line::from(vec![
+            "claimer_rs_full ".bold(),
+            state,
+            format!(
+                " · uptime {} · {} pseudos · q quitter · p pause · +/- concurrence",
+                format_uptime(data.stats.uptime_secs),
+                data.names_tracked
+            )
+            .into(),
+        ]),
+        header,
+    );
+
+    let widths = [Constraint::Length(7), Constraint::Length(7), Constraint::Length(8), Constraint::Length(8),
+        Constraint::Length(8), Constraint::Length(8), Constraint::Length(9), Constraint::Length(8), Constraint::Length(10)];
+    let throughput = Table::new(
+        [
+            throughput_row("1 min", &data.stats.last_1m),
+            throughput_row("15 min", &data.stats.last_15m),
+            throughput_row("1 h", &data.stats.last_1h),
+        ],
+        widths,
+    )
+    .header(Row::new(["", "req/s", "200", "429", "403", "autres", "batchs/s", "échecs", "pseudos/s"]).bold())
+    .block(Block::bordered().title(" Débit "));
+    frame.render_widget(throughput, top);
+
+    let [history, staleness, concurrency] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(35), Constraint::Percentage(20)]).areas(middle);
+    frame.render_widget(
+        Sparkline::default()
+            .data(&data.history)
+            .style(Style::default().fg(Color::Green))
+            .block(Block::bordered().title(" Requêtes 200 / 5 s (5 min) ")),
+        history,
+    );
+    frame.render_widget(
+        BarChart::default().data(data.staleness.as_slice()).bar_width(6).bar_gap(1).block(Block::bordered().title(" Fraîcheur ")),
+        staleness,
+    );
+    let usage = (data.in_flight * 100).checked_div(data.concurrency).unwrap_or(0);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!("limite  : {}", data.concurrency)),
+            Line::from(format!("en vol  : {} ({} %)", data.in_flight, usage)),
+            Line::from(if data.paused { "état    : pause" } else { "état    : actif" }),
+        ])
+        .block(Block::bordered().title(" Concurrence ")),
+        concurrency,
+    );
+
+    let [windows, proxies] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(bottom);
+    let rows: Vec<Row> = data.windows.iter().map(|w| window_row(w, data.now)).collect();
+    frame.render_widget(
+        Table::new(rows, [Constraint::Length(18), Constraint::Min(24), Constraint::Min(10)])
+            .header(Row::new(["pseudo", "fenêtre", "tags"]).bold())
+            .block(Block::bordered().title(" Prochaines fenêtres ")),
+        windows,
+    );
+    let p = &data.proxies;
+    let mut lines = vec![
+        Line::from(format!("{} proxies · {} inutilisés", p.total, p.unused)),
+        Line::from(vec![
+            format!("ok {}", p.healthy).green(),
+            " · ".into(),
+            format!("429 {}", p.rate_limited).yellow(),
+            " · ".into(),
+            format!("en échec {}", p.failing).red(),
+        ]),
+        Line::from("moins fiables :".dim()),
+    ];
+    lines.extend(p.worst.iter().map(|(label, ok, failed)| {
+        Line::from(format!("{label} {:.0} % ({ok}/{})", *ok as f64 * 100.0 / (ok + failed) as f64, ok + failed))
+    }));
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Proxies ")), proxies);
+
+    let visible = logs.height.saturating_sub(2) as usize;
+    let recent: Vec<Line> = data.logs.iter().skip(data.logs.len().saturating_sub(visible)).map(|l| Line::from(l.as_str())).collect();
+    frame.render_widget(Paragraph::new(recent).block(Block::bordered().title(" Événements récents ")), logs);
+}
+
+/// Affiche le tableau de bord jusqu'à ce que l'utilisateur quitte ; bloquant,
+/// à lancer via `spawn_blocking`.
+pub fn run_dashboard(sources: DashboardSources) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = (|| loop {
+        let data = DashboardData::collect(&sources);
+        terminal.draw(|frame| draw(frame, &data))?;
+        if !event::poll(Duration::from_millis(500))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let control = &sources.control;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('p') if control.is_paused() => {
+                control.resume();
+                info!("scanner relancé depuis le tableau de bord");
+            }
+            KeyCode::Char('p') => {
+                control.pause();
+                info!("scanner mis en pause depuis le tableau de bord");
+            }
+            KeyCode::Char('+') => {
+                let concurrency = control.set_concurrency(control.concurrency().saturating_mul(2));
+                info!(concurrency, "concurrence modifiée depuis le tableau de bord");
+            }
+            KeyCode::Char('-') => {
+                let concurrency = control.set_concurrency(control.concurrency() / 2);
+                info!(concurrency, "concurrence modifiée depuis le tableau de bord");
+            }
+            _ => {}
+        }
+    })();
+    ratatui::restore();
+    result
+}
+
+#[test]
+fn test_dashboard_render() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use super::sql_management::{insert_name, DropWindow};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    let names = Arc::new(UsernameMap::default());
+    insert_name(&names, "Dream");
+    insert_name(&names, "Notch");
+    let now = Utc::now();
+    names.get_mut("dream").unwrap().set_last_seen(now - chrono::Duration::minutes(3));
+    assert_eq!(staleness(&names, now), vec![("<1m", 0), ("1-5m", 1), ("5-15m", 0), ("15-60m", 0), (">1h", 0), ("jamais", 1)]);
+
+    let windows = Arc::new(WindowStore::open(super::storage::open_memory_db()?)?);
+    let begin = now + chrono::Duration::seconds(3_725);
+    windows.insert("Dream", &DropWindow { begin, end: begin + chrono::Duration::minutes(2) }, &["3c".to_string()])?;
+    let proxies = Arc::new(ProxyHealth::new(vec!["10.0.0.1:8080".into(), "10.0.0.2:8080".into()]));
+    proxies.record(0, super::stats::Outcome::Ok);
+    proxies.record(1, super::stats::Outcome::Timeout);
+    let sources = DashboardSources {
+        control: Arc::new(ScanControl::new(names.clone(), 100)),
+        names,
+        windows,
+        proxies,
+        logs: LogBuffer::default(),
+    };
+    let mut data = DashboardData::collect(&sources);
+    data.logs = vec!["INFO pseudo mis en quarantaine".into()];
+
+    let mut terminal = Terminal::new(TestBackend::new(140, 40))?;
+    terminal.draw(|frame| draw(frame, &data))?;
+    let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+    assert!(screen.contains("Prochaines fenêtres"));
+    assert!(screen.contains("Dream"));
+    assert!(screen.contains("dans 0D 01H 02m 0"));
+    assert!(screen.contains("ok 1 · 429 0 · en échec 1"));
+    assert!(screen.contains("10.0.0.2:8080 0 % (0/1)"));
+    assert!(screen.contains("INFO pseudo mis en quarantaine"));
+    Ok(())
+}
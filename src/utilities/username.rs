@@ -0,0 +1,118 @@
+use compact_str::CompactString;
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Pseudo normalisé : la clé (casse repliée) sert aux comparaisons et au
+/// hachage, la casse d'origine est conservée pour l'affichage et l'API.
+///
+/// `Borrow<str>` renvoie la clé, donc une map indexée par `Username` se
+/// consulte avec `map.get(username.key())` ou `map.get(&Username::key_of(raw))`.
+///
+/// La clé est stockée en ligne (jusqu'à 24 octets, sans allocation) et la
+/// casse d'affichage tient dans un masque de bits sur les 32 premiers
+/// caractères, ce qui couvre largement les pseudos Minecraft (16 max).
+#[derive(Clone, Debug)]
+pub struct Username {
+    key: CompactString,
+    upper: u32,
+}
+
+impl Username {
+    pub fn new(raw: &str) -> Self {
+        let raw = raw.trim();
+        Self { key: CompactString::from(Self::key_of(raw)), upper: case_mask(raw) }
+    }
+
+    /// Clé canonique d'un pseudo brut (les pseudos Minecraft sont insensibles à la casse).
+    pub fn key_of(raw: &str) -> String {
+        raw.trim().to_ascii_lowercase()
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn display(&self) -> Cow<'_, str> {
+        apply_case(&self.key, self.upper)
+    }
+}
+
+/// Masque des lettres ASCII majuscules d'un pseudo (bit i = caractère i).
+pub fn case_mask(raw: &str) -> u32 {
+    raw.bytes()
+        .take(32)
+        .enumerate()
+        .filter(|(_, b)| b.is_ascii_uppercase())
+        .fold(0, |mask, (i, _)| mask | (1 << i))
+}
+
+/// Réapplique un masque de casse sur une clé en minuscules.
+pub fn apply_case(key: &str, mask: u32) -> Cow<'_, str> {
+    if mask == 0 {
+        return Cow::Borrowed(key);
+    }
+    let bytes = key
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| if i < 32 && mask & (1 << i) != 0 { b.to_ascii_uppercase() } else { b })
+        .collect();
+    // seules des lettres ASCII changent de casse : l'UTF-8 reste valide
+    Cow::Owned(String::from_utf8(bytes).expect("ASCII case change keeps UTF-8 valid"))
+}
+
+impl PartialEq for Username {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Username {}
+
+impl Hash for Username {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // même hachage que `str` pour que `Borrow<str>` reste cohérent
+        self.key.as_str().hash(state)
+    }
+}
+
+impl Borrow<str> for Username {
+    fn borrow(&self) -> &str {
+        &self.key
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display())
+    }
+}
+
+impl From<&str> for Username {
+    fn from(raw: &str) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl From<String> for Username {
+    fn from(raw: String) -> Self {
+        Self::new(&raw)
+    }
+}
+
+#[test]
+fn test_username_normalization() {
+    use std::collections::HashMap;
+
+    let mixed = Username::new(" DrEaM ");
+    assert_eq!(mixed.key(), "dream");
+    assert_eq!(mixed.display(), "DrEaM");
+    assert_eq!(mixed, Username::new("dream"));
+    assert_eq!(Username::new("notch").display(), "notch");
+    assert_eq!(apply_case("jeb_", case_mask("JEB_")), "JEB_");
+
+    let mut map = HashMap::new();
+    map.insert(mixed, 1);
+    assert_eq!(map.get("dream"), Some(&1));
+    assert_eq!(map.get(Username::key_of("DREAM").as_str()), Some(&1));
+}
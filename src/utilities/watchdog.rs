@@ -0,0 +1,224 @@
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::control::ScanControl;
+use super::metrics::METRICS;
+use super::notifiers::{Dispatcher, Event};
+use super::stats::{StatsSnapshot, STATS};
+
+/// Surveillance du scanner : alerte quand plus aucun batch n'aboutit, quand
+/// la part de requêtes réussies s'effondre, quand le checkpoint ne tourne
+/// plus ou quand l'horloge locale dérive, puis avis de rétablissement quand
+/// le problème disparaît.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    /// Alerte si aucun batch n'a réussi depuis `stall_minutes`.
+    pub stall_minutes: i64,
+    /// Alerte si moins de `min_success_pct` % des requêtes des 15 dernières
+    /// minutes ont réussi…
+    pub min_success_pct: f64,
+    /// … sur au moins `min_requests` requêtes.
+    pub min_requests: u64,
+    /// Alerte si le checkpoint n'a pas tourné depuis `checkpoint_stall_minutes`.
+    pub checkpoint_stall_minutes: i64,
+    /// Alerte si l'horloge locale s'écarte de plus de `max_clock_skew_secs`
+    /// de celle du serveur (voir `clock`).
+    pub max_clock_skew_secs: f64,
+    pub check_interval_secs: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stall_minutes: 5,
+            min_success_pct: 10.0,
+            min_requests: 100,
+            checkpoint_stall_minutes: 5,
+            max_clock_skew_secs: 2.0,
+            check_interval_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Problem {
+    NoSuccess,
+    LowSuccessRate,
+    CheckpointStalled,
+    ClockSkew,
+}
+
+impl Problem {
+    const ALL: [Problem; 4] = [Problem::NoSuccess, Problem::LowSuccessRate, Problem::CheckpointStalled, Problem::ClockSkew];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Problem::NoSuccess => "aucun batch réussi",
+            Problem::LowSuccessRate => "taux de réussite faible",
+            Problem::CheckpointStalled => "checkpoint arrêté",
+            Problem::ClockSkew => "horloge décalée",
+        }
+    }
+}
+
+pub struct Watchdog {
+    config: WatchdogConfig,
+    /// Dernier checkpoint, en secondes Unix.
+    last_checkpoint: AtomicI64,
+    /// Problèmes signalés, depuis leur détection.
+    raised: Mutex<HashMap<Problem, DateTime<Utc>>>,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig, now: DateTime<Utc>) -> Self {
+        Self { config, last_checkpoint: AtomicI64::new(now.timestamp()), raised: Mutex::new(HashMap::new()) }
+    }
+
+    /// Appelé par la tâche du checkpoint à chaque tour.
+    pub fn checkpoint_done(&self) {
+        self.last_checkpoint.store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    /// Problèmes constatés à `now` ; en pause, seuls le checkpoint et
+    /// l'horloge sont surveillés.
+    fn problems(
+        &self,
+        now: DateTime<Utc>,
+        snapshot: &StatsSnapshot,
+        last_success: Option<DateTime<Utc>>,
+        paused: bool,
+    ) -> Vec<(Problem, String)> {
+        let mut found = Vec::new();
+        let silent = now.timestamp() - self.last_checkpoint.load(Ordering::Relaxed);
+        if silent > self.config.checkpoint_stall_minutes * 60 {
+            found.push((Problem::CheckpointStalled, format!("pas de checkpoint depuis {} min", silent / 60)));
+        }
+        if let Some(skew) = snapshot.clock_skew_ms.filter(|skew| skew.abs() as f64 > self.config.max_clock_skew_secs * 1000.0) {
+            let side = if skew > 0 { "en retard" } else { "en avance" };
+            found.push((Problem::ClockSkew, format!("horloge locale {} de {:.1} s sur le serveur", side, skew.abs() as f64 / 1000.0)));
+        }
+        if paused {
+            return found;
+        }
+        let since = last_success.unwrap_or(snapshot.started_at);
+        if now - since > Duration::minutes(self.config.stall_minutes) {
+            found.push((Problem::NoSuccess, format!("aucun batch réussi depuis {} min", (now - since).num_minutes())));
+        }
+        let counts = &snapshot.last_15m.counts;
+        let requests = counts.requests();
+        if requests >= self.config.min_requests.max(1) {
+            let pct = counts.ok as f64 / requests as f64 * 100.0;
+            if pct < self.config.min_success_pct {
+                found.push((
+                    Problem::LowSuccessRate,
+                    format!("{:.1} % de requêtes réussies sur 15 min ({} requêtes)", pct, requests),
+                ));
+            }
+        }
+        found
+    }
+
+    /// Un tour de surveillance : une alerte par nouveau problème, un avis de
+    /// rétablissement par problème disparu.
+    pub fn check(
+        &self,
+        now: DateTime<Utc>,
+        snapshot: &StatsSnapshot,
+        last_success: Option<DateTime<Utc>>,
+        paused: bool,
+    ) -> Vec<Event> {
+        let found = self.problems(now, snapshot, last_success, paused);
+        let mut raised = self.raised.lock();
+        let mut events = Vec::new();
+        for problem in Problem::ALL {
+            let current = found.iter().find(|(p, _)| *p == problem);
+            match (current, raised.get(&problem).copied()) {
+                (Some((_, detail)), None) => {
+                    warn!(problem = ?problem, detail = %detail, "watchdog : alerte");
+                    raised.insert(problem, now);
+                    events.push(Event::Watchdog { problem, recovered: false, since: now, detail: detail.clone() });
+                }
+                // en pause, l'état du scan reste figé
+                (None, Some(since)) if !paused || matches!(problem, Problem::CheckpointStalled | Problem::ClockSkew) => {
+                    info!(problem = ?problem, down_secs = (now - since).num_seconds(), "watchdog : rétabli");
+                    raised.remove(&problem);
+                    events.push(Event::Watchdog { problem, recovered: true, since, detail: String::new() });
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// Lance la surveillance en tâche de fond (sans effet si désactivée).
+pub fn spawn_watchdog(watchdog: Arc<Watchdog>, control: Arc<ScanControl>, dispatcher: Arc<Dispatcher>) {
+    if !watchdog.config.enabled {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(watchdog.config.check_interval_secs.max(1))).await;
+            let last_success = match METRICS.last_success.load(Ordering::Relaxed) {
+                0 => None,
+                secs => DateTime::from_timestamp(secs, 0),
+            };
+            for event in watchdog.check(Utc::now(), &STATS.snapshot(), last_success, control.is_paused()) {
+                dispatcher.emit(event).await;
+            }
+        }
+    });
+}
+
+#[test]
+fn test_watchdog_alerts_and_recovery() {
+    use super::stats::{Counts, WindowStats};
+
+    let start = DateTime::parse_from_rfc3339("2025-07-14T19:00:00Z").unwrap().with_timezone(&Utc);
+    let at = |minutes: i64| start + Duration::minutes(minutes);
+    let watchdog = Watchdog::new(WatchdogConfig::default(), start);
+    let snapshot = |ok: u64, errors: u64| StatsSnapshot {
+        started_at: start,
+        last_15m: WindowStats::new(Counts { ok, errors, ..Default::default() }, 900),
+        ..Default::default()
+    };
+
+    // au démarrage, laisse le temps au premier batch d'aboutir
+    assert!(watchdog.check(at(2), &snapshot(0, 0), None, false).is_empty());
+
+    // proxies morts : plus de réussite, 5 % de requêtes réussies
+    watchdog.last_checkpoint.store(at(6).timestamp(), Ordering::Relaxed);
+    let events = watchdog.check(at(6), &snapshot(50, 950), Some(at(0)), false);
+    let problems: Vec<_> = events.iter().filter_map(|e| match e {
+        Event::Watchdog { problem, recovered: false, .. } => Some(*problem),
+        _ => None,
+    }).collect();
+    assert_eq!(problems, vec![Problem::NoSuccess, Problem::LowSuccessRate]);
+    assert!(watchdog.check(at(7), &snapshot(50, 950), Some(at(0)), false).is_empty());
+
+    // en pause : rien ne change, sauf le checkpoint qui ne tourne plus
+    let events = watchdog.check(at(12), &snapshot(0, 0), Some(at(0)), true);
+    assert!(matches!(events.as_slice(), [Event::Watchdog { problem: Problem::CheckpointStalled, recovered: false, .. }]));
+
+    // horloge locale en retard de 3,5 s
+    let skewed = StatsSnapshot { clock_skew_ms: Some(3_500), ..snapshot(0, 0) };
+    let events = watchdog.check(at(12), &skewed, Some(at(0)), true);
+    assert!(matches!(events.as_slice(), [Event::Watchdog { problem: Problem::ClockSkew, detail, .. }]
+        if detail == "horloge locale en retard de 3.5 s sur le serveur"));
+
+    // reprise : tout est rétabli
+    watchdog.last_checkpoint.store(at(13).timestamp(), Ordering::Relaxed);
+    let events = watchdog.check(at(13), &snapshot(900, 100), Some(at(13)), false);
+    assert_eq!(events.len(), 4);
+    assert!(events.iter().all(|e| matches!(e, Event::Watchdog { recovered: true, .. })));
+    assert!(matches!(&events[0], Event::Watchdog { problem: Problem::NoSuccess, since, .. } if *since == at(6)));
+}
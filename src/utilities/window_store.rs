@@ -0,0 +1,294 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::clock::CLOCK;
+use super::notifiers::Event;
+use super::sql_management::{DropWindow, DropWindowRecord, WindowMap};
+use super::storage::{add_column_if_missing, Db};
+use super::username::Username;
+
+/// Cycle de vie d'une fenêtre de drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowStatus {
+    /// À venir ou en cours.
+    Open,
+    /// Le pseudo a retrouvé un UUID : quelqu'un l'a pris.
+    Claimed,
+    /// La fenêtre est terminée sans prise observée.
+    Closed,
+}
+
+impl WindowStatus {
+    fn parse(raw: &str) -> Self {
+        match raw {
+            "claimed" => WindowStatus::Claimed,
+            "closed" => WindowStatus::Closed,
+            _ => WindowStatus::Open,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredWindow {
+    pub username: String,
+    #[serde(flatten)]
+    pub window: DropWindow,
+    pub status: WindowStatus,
+    pub detected_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    /// Alerte prise en compte (`ack`) : plus d'escalade pour cette fenêtre.
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub escalated_at: Option<DateTime<Utc>>,
+    /// Nombre d'alertes d'escalade déjà envoyées.
+    pub escalations: u32,
+}
+
+const COLUMNS: &str =
+    "display, begin_at, end_at, status, detected_at, closed_at, tags, acknowledged_at, escalated_at, escalations";
+
+fn window_from_row(row: &Row<'_>) -> rusqlite::Result<StoredWindow> {
+    let status: String = row.get(3)?;
+    Ok(StoredWindow {
+        username: row.get(0)?,
+        window: DropWindow { begin: row.get(1)?, end: row.get(2)? },
+        status: WindowStatus::parse(&status),
+        detected_at: row.get(4)?,
+        closed_at: row.get(5)?,
+        tags: split_tags(&row.get::<_, String>(6)?),
+        acknowledged_at: row.get(7)?,
+        escalated_at: row.get(8)?,
+        escalations: row.get(9)?,
+    })
+}
+
+/// Tags stockés séparés par des virgules (un tag n'en contient jamais).
+fn split_tags(raw: &str) -> Vec<String> {
+    raw.split(',').filter(|tag| !tag.is_empty()).map(str::to_string).collect()
+}
+
+/// Fenêtres de drop persistées en SQLite, avec le suivi des rappels envoyés.
+pub struct WindowStore {
+    db: Db,
+}
+
+impl WindowStore {
+    pub fn open(db: Db) -> rusqlite::Result<Self> {
+        db.lock().execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS drop_windows (
+                username TEXT NOT NULL,
+                display TEXT NOT NULL,
+                begin_at TEXT NOT NULL,
+                end_at TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'open',
+                detected_at TEXT NOT NULL,
+                closed_at TEXT,
+                tags TEXT NOT NULL DEFAULT '',
+                acknowledged_at TEXT,
+                escalated_at TEXT,
+                escalations INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (username, begin_at)
+            );
+            CREATE INDEX IF NOT EXISTS drop_windows_status ON drop_windows (status, begin_at);
+            CREATE TABLE IF NOT EXISTS reminders_sent (
+                username TEXT NOT NULL,
+                begin_at TEXT NOT NULL,
+                offset_minutes INTEGER NOT NULL,
+                sent_at TEXT NOT NULL,
+                PRIMARY KEY (username, begin_at, offset_minutes)
+            );
+            ",
+        )?;
+        {
+            let conn = db.lock();
+            add_column_if_missing(&conn, "drop_windows", "tags", "TEXT NOT NULL DEFAULT ''")?;
+            add_column_if_missing(&conn, "drop_windows", "acknowledged_at", "TEXT")?;
+            add_column_if_missing(&conn, "drop_windows", "escalated_at", "TEXT")?;
+            add_column_if_missing(&conn, "drop_windows", "escalations", "INTEGER NOT NULL DEFAULT 0")?;
+        }
+        Ok(Self { db })
+    }
+
+    /// Répercute un événement sur les fenêtres stockées (nouvelle fenêtre, prise).
+    pub fn record(&self, event: &Event) -> rusqlite::Result<()> {
+        match event {
+            Event::DropWindow { username, window, tags } => self.insert(username, window, tags),
+            Event::Claimed { username, window, .. } => self.mark_claimed(username, window).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn insert(&self, username: &str, window: &DropWindow, tags: &[String]) -> rusqlite::Result<()> {
+        self.db.lock().execute(
+            "INSERT OR REPLACE INTO drop_windows (username, display, begin_at, end_at, status, detected_at, tags)
+             VALUES (?1, ?2, ?3, ?4, 'open', ?5, ?6)",
+            params![Username::key_of(username), username, window.begin, window.end, Utc::now(), tags.join(",")],
+        )?;
+        Ok(())
+    }
+
+    /// Marque comme prise la fenêtre d'un pseudo, ouverte ou terminée ; les
+    /// rappels en attente disparaissent avec le statut `open`. Les fenêtres
+    /// précédentes du pseudo gardent leur statut.
+    pub fn mark_claimed(&self, username: &str, window: &DropWindow) -> rusqlite::Result<usize> {
+        self.db.lock().execute(
+            "UPDATE drop_windows SET status = 'claimed', closed_at = ?3
+             WHERE username = ?1 AND begin_at = ?2 AND status IN ('open', 'closed')",
+            params![Username::key_of(username), window.begin, Utc::now()],
+        )
+    }
+
+    pub fn mark_closed(&self, username: &str, window: &DropWindow) -> rusqlite::Result<()> {
+        self.db.lock().execute(
+            "UPDATE drop_windows SET status = 'closed', closed_at = ?3
+             WHERE username = ?1 AND begin_at = ?2 AND status = 'open'",
+            params![Username::key_of(username), window.begin, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    /// Prend en compte les alertes des fenêtres ouvertes d'un pseudo, ce qui
+    /// arrête leur escalade. Renvoie le nombre de fenêtres concernées.
+    pub fn acknowledge(&self, username: &str) -> rusqlite::Result<usize> {
+        self.db.lock().execute(
+            "UPDATE drop_windows SET acknowledged_at = ?2
+             WHERE username = ?1 AND status = 'open' AND acknowledged_at IS NULL",
+            params![Username::key_of(username), Utc::now()],
+        )
+    }
+
+    pub fn mark_escalated(&self, username: &str, window: &DropWindow, at: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.db.lock().execute(
+            "UPDATE drop_windows SET escalated_at = ?3, escalations = escalations + 1
+             WHERE username = ?1 AND begin_at = ?2",
+            params![Username::key_of(username), window.begin, at],
+        )?;
+        Ok(())
+    }
+
+    pub fn open_windows(&self) -> rusqlite::Result<Vec<StoredWindow>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {COLUMNS} FROM drop_windows WHERE status = 'open' ORDER BY begin_at"
+        ))?;
+        let rows = stmt.query_map([], window_from_row)?;
+        rows.collect()
+    }
+
+    /// Toutes les fenêtres (les `limit` dernières si précisé), les plus
+    /// récentes d'abord.
+    pub fn all(&self, limit: Option<usize>) -> rusqlite::Result<Vec<StoredWindow>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare_cached(&format!("SELECT {COLUMNS} FROM drop_windows ORDER BY begin_at DESC LIMIT ?1"))?;
+        // LIMIT -1 : pas de limite pour SQLite
+        let rows = stmt.query_map(params![limit.map_or(-1, |n| n as i64)], window_from_row)?;
+        rows.collect()
+    }
+
+    /// Fenêtres qui s'ouvrent puis qui se terminent dans `[from, to[`.
+    pub fn count_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> rusqlite::Result<(u64, u64)> {
+        self.db.lock().query_row(
+            "SELECT
+                 COALESCE(SUM(begin_at >= ?1 AND begin_at < ?2), 0),
+                 COALESCE(SUM(end_at >= ?1 AND end_at < ?2), 0)
+             FROM drop_windows",
+            params![from, to],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+
+    pub fn latest(&self, username: &str) -> rusqlite::Result<Option<StoredWindow>> {
+        self.db
+            .lock()
+            .query_row(
+                &format!("SELECT {COLUMNS} FROM drop_windows WHERE username = ?1 ORDER BY begin_at DESC LIMIT 1"),
+                params![Username::key_of(username)],
+                window_from_row,
+            )
+            .optional()
+    }
+
+    /// Importe les fenêtres de `drop_windows.txt` si la base n'en contient
+    /// aucune (migration depuis les versions sans base).
+    pub fn import_if_empty(&self, records: &[DropWindowRecord]) -> rusqlite::Result<usize> {
+        let count: i64 = self.db.lock().query_row("SELECT COUNT(*) FROM drop_windows", [], |row| row.get(0))?;
+        if count > 0 {
+            return Ok(0);
+        }
+        let now = CLOCK.corrected(Utc::now());
+        for record in records {
+            self.insert(&record.username, &record.window, &[])?;
+            if record.window.end <= now {
+                self.mark_closed(&record.username, &record.window)?;
+            }
+        }
+        Ok(records.len())
+    }
+
+    /// Recharge dans la map utilisée pour détecter les prises la dernière
+    /// fenêtre de chaque pseudo pas encore repris, ouverte ou déjà terminée.
+    pub fn load_into(&self, map_windows: &WindowMap) -> rusqlite::Result<usize> {
+        let unclaimed: Vec<StoredWindow> = {
+            let conn = self.db.lock();
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {COLUMNS} FROM drop_windows AS w WHERE status IN ('open', 'closed')
+                 AND begin_at = (SELECT MAX(begin_at) FROM drop_windows WHERE username = w.username)"
+            ))?;
+            let rows = stmt.query_map([], window_from_row)?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        for stored in &unclaimed {
+            map_windows.insert(Username::new(&stored.username), stored.window);
+        }
+        Ok(unclaimed.len())
+    }
+
+    pub fn reminder_sent(&self, username: &str, window: &DropWindow, offset_minutes: i64) -> rusqlite::Result<bool> {
+        self.db
+            .lock()
+            .query_row(
+                "SELECT 1 FROM reminders_sent WHERE username = ?1 AND begin_at = ?2 AND offset_minutes = ?3",
+                params![Username::key_of(username), window.begin, offset_minutes],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|found| found.is_some())
+    }
+
+    pub fn mark_reminder_sent(&self, username: &str, window: &DropWindow, offset_minutes: i64) -> rusqlite::Result<()> {
+        self.db.lock().execute(
+            "INSERT OR IGNORE INTO reminders_sent (username, begin_at, offset_minutes, sent_at) VALUES (?1, ?2, ?3, ?4)",
+            params![Username::key_of(username), window.begin, offset_minutes, Utc::now()],
+        )?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_claim_marks_latest_window_only() -> rusqlite::Result<()> {
+    use chrono::Duration;
+
+    let store = WindowStore::open(super::storage::open_memory_db()?)?;
+    let now = Utc::now();
+    let old = DropWindow { begin: now - Duration::days(60), end: now - Duration::days(59) };
+    let last = DropWindow { begin: now - Duration::hours(2), end: now - Duration::hours(1) };
+    for window in [&old, &last] {
+        store.insert("Dream", window, &[])?;
+        store.mark_closed("Dream", window)?;
+    }
+
+    // pris après la fin de sa dernière fenêtre, détecté après un redémarrage
+    let map_windows = WindowMap::default();
+    assert_eq!(store.load_into(&map_windows)?, 1);
+    assert_eq!(map_windows.get("dream").map(|w| w.begin), Some(last.begin));
+    assert_eq!(store.mark_claimed("Dream", &last)?, 1);
+
+    let statuses: Vec<_> = store.all(None)?.into_iter().map(|w| w.status).collect();
+    assert_eq!(statuses, vec![WindowStatus::Claimed, WindowStatus::Closed]);
+    assert_eq!(store.all(Some(1))?.len(), 1);
+    assert_eq!(store.load_into(&WindowMap::default())?, 0);
+    Ok(())
+}